@@ -3,9 +3,12 @@ use soroban_sdk::{
     contract, contracterror, contractimpl, symbol_short, Address, Bytes, BytesN, Env, Symbol,
 };
 
+#[allow(clippy::too_many_arguments)]
 mod wallet {
     use soroban_sdk::auth::Context;
-    soroban_sdk::contractimport!(file = "../target/wasm32-unknown-unknown/release/webauthn_wallet.wasm");
+    soroban_sdk::contractimport!(
+        file = "../target/wasm32-unknown-unknown/release/webauthn_wallet.wasm"
+    );
 }
 
 #[contract]
@@ -20,6 +23,7 @@ pub enum Error {
 
 const WEEK_OF_LEDGERS: u32 = 60 * 60 * 24 / 5 * 7;
 const STORAGE_KEY_WASM_HASH: Symbol = symbol_short!("hash");
+const STORAGE_KEY_RP_ID_HASH: Symbol = symbol_short!("rp_id");
 
 /* NOTE
     - We don't have an upgrade function here because if we want to make a new wallet printer we should just deploy an entirely new one
@@ -36,7 +40,11 @@ const STORAGE_KEY_WASM_HASH: Symbol = symbol_short!("hash");
 
 #[contractimpl]
 impl Contract {
-    pub fn init(env: Env, wasm_hash: BytesN<32>) -> Result<(), Error> {
+    pub fn init(
+        env: Env,
+        wasm_hash: BytesN<32>,
+        rp_id_hash: Option<BytesN<32>>,
+    ) -> Result<(), Error> {
         if env.storage().instance().has(&STORAGE_KEY_WASM_HASH) {
             return Err(Error::AlreadyInitialized);
         }
@@ -47,6 +55,13 @@ impl Contract {
             .instance()
             .set(&STORAGE_KEY_WASM_HASH, &wasm_hash);
 
+        // Wallets will only accept passkeys made for this relying party
+        if let Some(rp_id_hash) = rp_id_hash {
+            env.storage()
+                .instance()
+                .set(&STORAGE_KEY_RP_ID_HASH, &rp_id_hash);
+        }
+
         env.storage()
             .instance()
             .extend_ttl(max_ttl - WEEK_OF_LEDGERS, max_ttl);
@@ -63,7 +78,14 @@ impl Contract {
 
         let address = env.deployer().with_current_contract(salt).deploy(wasm_hash);
 
-        wallet::Client::new(&env, &address).add(&id, &pk, &true);
+        let wallet = wallet::Client::new(&env, &address);
+        let rp_id_hash = env
+            .storage()
+            .instance()
+            .get::<Symbol, BytesN<32>>(&STORAGE_KEY_RP_ID_HASH);
+
+        // Deploying and initializing in the same call leaves no window for anyone else to claim the wallet
+        wallet.init(&id, &wallet::SignerKey::Secp256r1(pk), &rp_id_hash);
 
         let max_ttl = env.storage().max_ttl();
 
@@ -0,0 +1,203 @@
+// A minimal reader for the WebAuthn clientDataJSON, extracting only the members the wallet checks.
+//
+// Works directly over `Bytes` a byte at a time so the JSON's size isn't tied to a stack buffer.
+// Everything it doesn't need (extra members, nested objects, arrays, numbers, null) is skipped.
+// String values are returned raw, escapes included, as none of the members we check ever contain any.
+
+use soroban_sdk::Bytes;
+
+use crate::Error;
+
+// Browsers keep clientDataJSON to a few hundred bytes, this leaves plenty of room for extra members and long origins
+pub const MAX_LEN: u32 = 4096;
+
+pub struct ClientDataJson {
+    pub type_: Bytes,
+    pub challenge: Bytes,
+    pub origin: Bytes,
+    pub cross_origin: bool,
+}
+
+pub fn parse(client_data_json: &Bytes) -> Result<ClientDataJson, Error> {
+    if client_data_json.len() > MAX_LEN {
+        return Err(Error::ClientDataJsonTooLong);
+    }
+
+    let mut reader = Reader::new(client_data_json);
+    let mut type_ = None;
+    let mut challenge = None;
+    let mut origin = None;
+    let mut cross_origin = None;
+
+    reader.expect(b'{')?;
+
+    if reader.peek_token() == Some(b'}') {
+        reader.next();
+    } else {
+        loop {
+            reader.expect(b'"')?;
+
+            // Compared by the host, which takes less wasm than copying the name out to compare it here
+            let (start, end) = reader.string_range()?;
+            let key = client_data_json.slice(start..end);
+            let is = |name: &str| key == Bytes::from_slice(client_data_json.env(), name.as_bytes());
+
+            reader.expect(b':')?;
+
+            let field = if is("type") {
+                Some(&mut type_)
+            } else if is("challenge") {
+                Some(&mut challenge)
+            } else if is("origin") {
+                Some(&mut origin)
+            } else {
+                None
+            };
+
+            match field {
+                Some(field) => {
+                    // Duplicated members could be read differently by different parsers
+                    if field.is_some() {
+                        return Err(Error::JsonParseError);
+                    }
+
+                    reader.expect(b'"')?;
+
+                    let (start, end) = reader.string_range()?;
+
+                    *field = Some(client_data_json.slice(start..end));
+                }
+                None if is("crossOrigin") => {
+                    if cross_origin.is_some() {
+                        return Err(Error::JsonParseError);
+                    }
+
+                    cross_origin = Some(reader.boolean()?);
+                }
+                None => reader.skip_value()?,
+            }
+
+            match reader.next_token() {
+                Some(b',') => {}
+                Some(b'}') => break,
+                _ => return Err(Error::JsonParseError),
+            }
+        }
+    }
+
+    // Nothing but whitespace may follow the object
+    if reader.peek_token().is_some() {
+        return Err(Error::JsonParseError);
+    }
+
+    Ok(ClientDataJson {
+        type_: type_.ok_or(Error::JsonParseError)?,
+        challenge: challenge.ok_or(Error::JsonParseError)?,
+        origin: origin.ok_or(Error::JsonParseError)?,
+        cross_origin: cross_origin.unwrap_or(false),
+    })
+}
+
+struct Reader<'a> {
+    bytes: &'a Bytes,
+    pos: u32,
+}
+
+impl<'a> Reader<'a> {
+    fn new(bytes: &'a Bytes) -> Self {
+        Reader { bytes, pos: 0 }
+    }
+
+    fn peek(&self) -> Option<u8> {
+        self.bytes.get(self.pos)
+    }
+
+    fn next(&mut self) -> Option<u8> {
+        let byte = self.peek()?;
+
+        self.pos += 1;
+
+        Some(byte)
+    }
+
+    fn skip_whitespace(&mut self) {
+        while let Some(b' ' | b'\t' | b'\n' | b'\r') = self.peek() {
+            self.pos += 1;
+        }
+    }
+
+    fn peek_token(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.peek()
+    }
+
+    fn next_token(&mut self) -> Option<u8> {
+        self.skip_whitespace();
+        self.next()
+    }
+
+    fn expect(&mut self, token: u8) -> Result<(), Error> {
+        match self.next_token() {
+            Some(byte) if byte == token => Ok(()),
+            _ => Err(Error::JsonParseError),
+        }
+    }
+
+    // Reads up to the closing quote of a string whose opening quote has already been read
+    fn string_range(&mut self) -> Result<(u32, u32), Error> {
+        let start = self.pos;
+
+        loop {
+            match self.next() {
+                Some(b'"') => return Ok((start, self.pos - 1)),
+                Some(b'\\') => {
+                    self.next().ok_or(Error::JsonParseError)?;
+                }
+                Some(byte) if byte < 0x20 => return Err(Error::JsonParseError),
+                Some(_) => {}
+                None => return Err(Error::JsonParseError),
+            }
+        }
+    }
+
+    fn literal(&mut self, literal: &[u8]) -> Result<(), Error> {
+        for expected in literal {
+            if self.next() != Some(*expected) {
+                return Err(Error::JsonParseError);
+            }
+        }
+
+        Ok(())
+    }
+
+    fn boolean(&mut self) -> Result<bool, Error> {
+        match self.peek_token() {
+            Some(b't') => self.literal(b"true").map(|_| true),
+            Some(b'f') => self.literal(b"false").map(|_| false),
+            _ => Err(Error::JsonParseError),
+        }
+    }
+
+    // Skips over any other value, nested objects and arrays included, up to the `,` or `}` that ends its member.
+    // Nothing in it is ever read, so beyond terminated strings and balanced brackets it isn't checked
+    fn skip_value(&mut self) -> Result<(), Error> {
+        let mut depth: u32 = 0;
+
+        loop {
+            match self.peek().ok_or(Error::JsonParseError)? {
+                b',' | b'}' if depth == 0 => return Ok(()),
+                b'"' => {
+                    self.pos += 1;
+                    self.string_range()?;
+
+                    continue;
+                }
+                b'{' | b'[' => depth += 1,
+                b'}' | b']' => depth = depth.checked_sub(1).ok_or(Error::JsonParseError)?,
+                _ => {}
+            }
+
+            self.pos += 1;
+        }
+    }
+}
@@ -1,13 +1,20 @@
 #![no_std]
 
 use soroban_sdk::{
-    auth::{Context, CustomAccountInterface},
-    contract, contracterror, contractimpl, contracttype,
+    auth::{Context, ContractContext, CustomAccountInterface},
+    contract, contractclient, contracterror, contractimpl, contracttype,
     crypto::Hash,
-    panic_with_error, symbol_short, Bytes, BytesN, Env, FromVal, Symbol, Vec,
+    panic_with_error, symbol_short,
+    unwrap::UnwrapOptimized,
+    vec,
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, FromVal, IntoVal, Map, String, Symbol, TryFromVal, TryIntoVal,
+    Val, Vec,
 };
 
 mod base64_url;
+mod client_data_json;
+mod secp256r1;
 
 mod test;
 
@@ -23,214 +30,2216 @@ pub enum Error {
     ClientDataJsonChallengeIncorrect = 3,
     Secp256r1PublicKeyParse = 4,
     Secp256r1SignatureParse = 5,
+    // Reserved, `secp256r1_verify` traps on a bad signature rather than returning an error
     Secp256r1VerifyFailed = 6,
     JsonParseError = 7,
+    SignatureKeyMismatch = 8,
+    SignerDuplicated = 9,
+    ThresholdNotMet = 10,
+    InvalidThreshold = 11,
+    InvalidWeight = 12,
+    PolicyRejected = 13,
+    SpendLimitExceeded = 14,
+    SignerNotYetValid = 15,
+    SignerExpired = 16,
+    RecoveryPending = 17,
+    RecoveryNotReady = 18,
+    TimelockNotReady = 19,
+    AuthenticatorDataInvalid = 20,
+    SignCountInvalid = 21,
+    RpIdHashMismatch = 22,
+    UserPresenceRequired = 23,
+    UserVerificationRequired = 24,
+    ClientDataJsonTypeIncorrect = 25,
+    ClientDataJsonOriginNotAllowed = 26,
+    ClientDataJsonCrossOrigin = 27,
+    ClientDataJsonTooLong = 28,
+    LabelTooLong = 29,
+    AlreadyInitialized = 30,
+}
+
+// Every storage key, so new state can never collide with signer ids or each other.
+// Like the other storage-only types it's kept out of the contract spec, which counts towards `contract_max_size_bytes`
+#[contracttype(export = false)]
+#[derive(Clone)]
+pub enum DataKey {
+    Signer(Bytes),          // persistent for admins, temporary for session signers
+    Guardian(Bytes),        // persistent
+    SignCount(Bytes),       // persistent
+    SignRoot(Bytes),        // temporary, the Merkle root last signed with the current sign count
+    DelegationEpoch(Bytes), // persistent, bumped to revoke every delegation a signer has certified
+    LastUsed(Bytes),        // persistent
+    Spent(Bytes, Address),  // temporary, by signer and token
+    Pending(u32),           // persistent, by op id
+    Signers,                // persistent
+    AdminCount,
+    AdminWeight,
+    Thresholds,
+    GuardianCount,
+    Recovery,
+    PendingRecovery,
+    Timelock,
+    OpCount,
+    RpIdHash,
+    UserVerification,
+    Origins,
+    CrossOrigin,
+    LegacySigners, // until `migrate` has run on a wallet deployed before `DataKey`
 }
 
 const WEEK_OF_LEDGERS: u32 = 60 * 60 * 24 / 5 * 7;
 const EVENT_TAG: Symbol = symbol_short!("sw_v1");
-const ADMIN_SIGNER_COUNT: Symbol = symbol_short!("admins");
+// Wallets deployed before `DataKey` kept their admin count here and each signer's public key under its raw id
+const LEGACY_ADMIN_SIGNER_COUNT: Symbol = symbol_short!("admins");
+const MAX_LABEL_LEN: u32 = 32;
+const MAX_DELEGATION_LEDGERS: u32 = WEEK_OF_LEDGERS;
+const FLAG_USER_PRESENT: u8 = 0x01;
+const FLAG_USER_VERIFIED: u8 = 0x04;
+const TRANSFER_FNS: [&str; 5] = ["transfer", "transfer_from", "approve", "burn", "burn_from"];
 
 #[contractimpl]
 impl Contract {
-    pub fn add(env: Env, id: Bytes, pk: BytesN<65>, mut admin: bool) -> Result<(), Error> {
-        if env.storage().instance().has(&ADMIN_SIGNER_COUNT) {
-            env.current_contract_address().require_auth();
-        } else {
-            admin = true; // Ensure if this is the first signer they are an admin
+    // Sets up the first admin, and optionally the relying party, in the same call the wallet is deployed in so no one else can claim it
+    pub fn init(
+        env: Env,
+        id: Bytes,
+        key: SignerKey,
+        rp_id_hash: Option<BytesN<32>>,
+    ) -> Result<(), Error> {
+        // A wallet that hasn't been migrated yet mustn't look like it has no admins
+        Self::migrate_instance(&env);
+
+        if env.storage().instance().has(&DataKey::AdminCount) {
+            return Err(Error::AlreadyInitialized);
         }
 
-        let max_ttl = env.storage().max_ttl();
+        key.check()?;
+
+        Self::store_signer(&env, &id, &Signer::new(&env, key, 1), true);
+        Self::store_webauthn(&env, rp_id_hash, false);
+
+        Self::extend_instance(&env);
+
+        Ok(())
+    }
+    #[allow(clippy::too_many_arguments)]
+    pub fn add(
+        env: Env,
+        id: Bytes,
+        key: SignerKey,
+        admin: bool,
+        weight: u32,
+        valid_after: Option<u32>,
+        valid_until: Option<u32>,
+        label: Option<String>,
+    ) -> Result<(), Error> {
+        env.current_contract_address().require_auth();
+
+        if weight == 0 {
+            return Err(Error::InvalidWeight);
+        }
+
+        // Admin weight is counted towards thresholds as a whole, so admins can't be allowed to lapse, see `set_thresholds`
+        if admin && (valid_after.is_some() || valid_until.is_some()) {
+            return Err(Error::NotPermitted);
+        }
+
+        key.check()?;
+
+        // Guardians' sign counts mustn't be reset from under them, see `add_guardian`
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::Guardian(id.clone()))
+        {
+            return Err(Error::SignerDuplicated);
+        }
+
+        Self::migrate_signer(&env, &id);
 
+        let label = label.unwrap_or(String::from_str(&env, ""));
+
+        if label.len() > MAX_LABEL_LEN {
+            return Err(Error::LabelTooLong);
+        }
+
+        let signer = Signer {
+            valid_after: valid_after.unwrap_or(0),
+            valid_until: valid_until.unwrap_or(u32::MAX),
+            label,
+            ..Signer::new(&env, key, weight)
+        };
+
+        // Don't add signers which could never be used
+        if signer.valid_until < env.ledger().sequence() || signer.valid_after > signer.valid_until {
+            return Err(Error::SignerExpired);
+        }
+
+        // The admins' combined weight must still fit, see `update_admin_signers`
         if admin {
-            if env.storage().temporary().has(&id) {
-                env.storage().temporary().remove(&id);
+            let previous = match Self::load_signer(&env, &id) {
+                Some((true, previous)) => previous.weight,
+                _ => 0,
+            };
+
+            if (Self::get_admin_weight(&env).saturating_sub(previous))
+                .checked_add(weight)
+                .is_none()
+            {
+                return Err(Error::InvalidWeight);
             }
+        }
 
-            Self::update_admin_signer_count(&env, true);
+        // Adding, changing or demoting an admin waits out the timelock
+        if Self::get_timelock(&env) > 0
+            && (admin || env.storage().persistent().has(&DataKey::Signer(id.clone())))
+        {
+            return Self::queue(&env, AdminOp::Add(id, signer, admin));
+        }
 
-            env.storage().persistent().set(&id, &pk);
+        Self::store_signer(&env, &id, &signer, admin);
 
-            env.storage()
-                .persistent()
-                .extend_ttl(&id, max_ttl - WEEK_OF_LEDGERS, max_ttl);
-        } else {
-            if env.storage().persistent().has(&id) {
-                Self::update_admin_signer_count(&env, false);
+        Self::extend_instance(&env);
 
-                env.storage().persistent().remove(&id);
-            }
+        Ok(())
+    }
+    pub fn remove(env: Env, id: Bytes) -> Result<(), Error> {
+        env.current_contract_address().require_auth();
 
-            env.storage().temporary().set(&id, &pk);
+        Self::migrate_signer(&env, &id);
 
-            env.storage()
-                .temporary()
-                .extend_ttl(&id, max_ttl - WEEK_OF_LEDGERS, max_ttl);
+        let key = DataKey::Signer(id.clone());
+        // Removing an admin waits out the timelock
+        if Self::get_timelock(&env) > 0
+            && !env.storage().temporary().has(&key)
+            && env.storage().persistent().has(&key)
+        {
+            return Self::queue(&env, AdminOp::Remove(id));
+        }
+
+        Self::remove_signer(&env, id);
+
+        Self::extend_instance(&env);
+
+        Ok(())
+    }
+    // Swaps a signer's id and key in place, keeping everything else about it
+    pub fn rotate(env: Env, old_id: Bytes, new_id: Bytes, new_key: SignerKey) -> Result<(), Error> {
+        env.current_contract_address().require_auth();
+
+        new_key.check()?;
+
+        Self::migrate_signer(&env, &old_id);
+
+        let (admin, _) = Self::check_rotation(&env, &old_id, &new_id)?;
+
+        if admin && Self::get_timelock(&env) > 0 {
+            return Self::queue(&env, AdminOp::Rotate(old_id, new_id, new_key));
+        }
+
+        Self::rotate_signer(&env, old_id, new_id, new_key)?;
+
+        Self::extend_instance(&env);
+
+        Ok(())
+    }
+    pub fn get_signer(env: Env, id: Bytes) -> Result<SignerInfo, Error> {
+        let (admin, signer) = Self::load_signer(&env, &id).ok_or(Error::NotFound)?;
+        let last_used = env
+            .storage()
+            .persistent()
+            .get::<_, u32>(&DataKey::LastUsed(id.clone()))
+            .unwrap_or(0);
+
+        Ok(SignerInfo {
+            id,
+            admin,
+            signer,
+            last_used,
+        })
+    }
+    pub fn list_signers(env: Env) -> Vec<SignerInfo> {
+        let mut signers: Vec<SignerInfo> = Vec::new(&env);
+
+        for id in env
+            .storage()
+            .persistent()
+            .get::<DataKey, Vec<Bytes>>(&DataKey::Signers)
+            .unwrap_or(Vec::new(&env))
+            .items()
+        {
+            // Session signers may have expired since the index was last written
+            if let Ok(signer) = Self::get_signer(env.clone(), id) {
+                signers.push_back(signer);
+            }
         }
 
+        signers
+    }
+    pub fn admin_count(env: Env) -> u32 {
         env.storage()
             .instance()
-            .extend_ttl(max_ttl - WEEK_OF_LEDGERS, max_ttl);
+            .get::<DataKey, i32>(&DataKey::AdminCount)
+            .unwrap_or(0) as u32
+    }
+    // Lets dApps check messages signed off-chain with the wallet, e.g. login challenges, through simulation.
+    // Only signers unrestricted by scopes, limits, policies or delegation vouch for the wallet.
+    // Anything wrong with the signature short of the cryptography returns false, but a signature the host rejects traps, as in `__check_auth`
+    pub fn is_valid_signature(env: Env, hash: BytesN<32>, signature: Signature) -> bool {
+        Self::signature_weight(&env, &hash, &signature)
+            .is_ok_and(|weight| weight >= Self::get_thresholds(&env).default)
+    }
+    // The same for wallets whose default threshold takes more than one signer, their weights are summed as in `__check_auth`
+    pub fn is_valid_signatures(env: Env, hash: BytesN<32>, signatures: Vec<Signature>) -> bool {
+        Self::check_signatures(&env, &hash, &signatures).is_ok()
+    }
+    // Makes each call in order as this wallet, all under the one authorization, see `__check_auth`
+    pub fn exec(env: Env, calls: Vec<(Address, Symbol, Vec<Val>)>) -> Result<Vec<Val>, Error> {
+        env.current_contract_address().require_auth();
+
+        let mut results: Vec<Val> = Vec::new(&env);
+
+        for (contract, fn_name, args) in calls.items() {
+            results.push_back(env.invoke_contract::<Val>(&contract, &fn_name, args));
+        }
+
+        Self::extend_instance(&env);
+
+        Ok(results)
+    }
+    pub fn set_policies(env: Env, id: Bytes, policies: Vec<Address>) -> Result<(), Error> {
+        env.current_contract_address().require_auth();
+
+        Self::migrate_signer(&env, &id);
+
+        let (admin, mut signer) = Self::load_signer(&env, &id).ok_or(Error::NotFound)?;
+
+        signer.policies = policies.clone();
+
+        Self::save_signer(&env, &id, &signer, admin);
+
+        Self::extend_instance(&env);
 
-        // TEMP until Zephyr fixes their event processing system to allow for bytesn arrays in the data field
-        // env.events()
-        //     .publish((EVENT_TAG, symbol_short!("add"), id), (pk, admin));
         env.events()
-            .publish((EVENT_TAG, symbol_short!("add"), id, pk), admin);
+            .publish((EVENT_TAG, symbol_short!("policies"), id), policies);
 
         Ok(())
     }
-    pub fn remove(env: Env, id: Bytes) -> Result<(), Error> {
+    pub fn set_limits(env: Env, id: Bytes, limits: Map<Address, SpendLimit>) -> Result<(), Error> {
+        env.current_contract_address().require_auth();
+
+        Self::migrate_signer(&env, &id);
+
+        // Spend limits only apply to session signers
+        let mut signer = match Self::load_signer(&env, &id) {
+            Some((false, signer)) => signer,
+            _ => return Err(Error::NotFound),
+        };
+
+        signer.limits = limits.clone();
+
+        Self::save_signer(&env, &id, &signer, false);
+
+        Self::extend_instance(&env);
+
+        env.events()
+            .publish((EVENT_TAG, symbol_short!("limits"), id), limits);
+
+        Ok(())
+    }
+    pub fn set_scope(env: Env, id: Bytes, scope: Map<Address, Vec<Symbol>>) -> Result<(), Error> {
+        env.current_contract_address().require_auth();
+
+        Self::migrate_signer(&env, &id);
+
+        // Scopes only apply to session signers
+        let mut signer = match Self::load_signer(&env, &id) {
+            Some((false, signer)) => signer,
+            _ => return Err(Error::NotFound),
+        };
+
+        signer.scope = scope.clone();
+
+        Self::save_signer(&env, &id, &signer, false);
+
+        Self::extend_instance(&env);
+
+        env.events()
+            .publish((EVENT_TAG, symbol_short!("scope"), id), scope);
+
+        Ok(())
+    }
+    // Invalidates every delegation the signer has certified so far, see `Delegation`
+    pub fn revoke_delegations(env: Env, id: Bytes) -> Result<(), Error> {
         env.current_contract_address().require_auth();
 
-        if env.storage().temporary().has(&id) {
-            env.storage().temporary().remove(&id);
-        } else if env.storage().persistent().has(&id) {
-            Self::update_admin_signer_count(&env, false);
+        Self::migrate_signer(&env, &id);
 
-            env.storage().persistent().remove(&id);
+        if !Self::has_signer(&env, &id) {
+            return Err(Error::NotFound);
         }
 
-        let max_ttl = env.storage().max_ttl();
+        let key = DataKey::DelegationEpoch(id.clone());
+        let epoch = env.storage().persistent().get::<_, u32>(&key).unwrap_or(0) + 1;
 
-        env.storage()
-            .instance()
-            .extend_ttl(max_ttl - WEEK_OF_LEDGERS, max_ttl);
+        env.storage().persistent().set(&key, &epoch);
+
+        Self::extend_persistent(&env, &key);
+
+        Self::extend_instance(&env);
 
         env.events()
-            .publish((EVENT_TAG, symbol_short!("remove"), id), ());
+            .publish((EVENT_TAG, symbol_short!("revoke"), id), epoch);
 
         Ok(())
     }
     pub fn update(env: Env, hash: BytesN<32>) -> Result<(), Error> {
         env.current_contract_address().require_auth();
 
+        if Self::get_timelock(&env) > 0 {
+            return Self::queue(&env, AdminOp::Update(hash));
+        }
+
         env.deployer().update_current_contract_wasm(hash);
 
-        let max_ttl = env.storage().max_ttl();
+        Self::extend_instance(&env);
+
+        Ok(())
+    }
+    // The new code only runs once `update` has returned, so a wallet upgraded from before `DataKey` calls this next.
+    // Signers are also migrated as they're used or changed, this is for the rest. Ids with nothing to migrate are skipped.
+    // Any legacy signer left out is left behind for good, raw ids are no longer looked up once this has run.
+    // The admin count and weight are then recounted from the migrated admins, see `reconcile_admin_signers`.
+    // Called with no ids it's just that recount, for wallets whose count drifted after they were migrated
+    pub fn migrate(env: Env, ids: Vec<Bytes>) -> Result<(), Error> {
+        env.current_contract_address().require_auth();
+
+        Self::migrate_instance(&env);
+
+        for id in ids.items() {
+            Self::migrate_signer(&env, &id);
+        }
+
+        env.storage().instance().remove(&DataKey::LegacySigners);
+
+        Self::reconcile_admin_signers(&env)?;
+
+        Self::extend_instance(&env);
+
+        Ok(())
+    }
+    pub fn set_webauthn(
+        env: Env,
+        rp_id_hash: Option<BytesN<32>>,
+        user_verification: bool,
+    ) -> Result<(), Error> {
+        env.current_contract_address().require_auth();
+
+        Self::store_webauthn(&env, rp_id_hash, user_verification);
+
+        Self::extend_instance(&env);
+
+        Ok(())
+    }
+    pub fn set_origins(env: Env, origins: Vec<Bytes>, cross_origin: bool) -> Result<(), Error> {
+        env.current_contract_address().require_auth();
 
         env.storage()
             .instance()
-            .extend_ttl(max_ttl - WEEK_OF_LEDGERS, max_ttl);
+            .set::<DataKey, Vec<Bytes>>(&DataKey::Origins, &origins);
+        env.storage()
+            .instance()
+            .set::<DataKey, bool>(&DataKey::CrossOrigin, &cross_origin);
+
+        Self::extend_instance(&env);
+
+        env.events().publish(
+            (EVENT_TAG, symbol_short!("origins")),
+            (origins, cross_origin),
+        );
 
         Ok(())
     }
-    fn update_admin_signer_count(env: &Env, add: bool) {
-        let count = env
-            .storage()
-            .instance()
-            .get::<Symbol, i32>(&ADMIN_SIGNER_COUNT)
-            .unwrap_or(0)
-            + if add { 1 } else { -1 };
+    pub fn set_thresholds(env: Env, thresholds: Thresholds) -> Result<(), Error> {
+        env.current_contract_address().require_auth();
 
-        if count <= 0 {
-            panic_with_error!(env, Error::NotPermitted)
+        let weight = Self::get_admin_weight(&env);
+
+        // Admins must always be able to meet every threshold on their own
+        if thresholds.admin == 0
+            || thresholds.transfer == 0
+            || thresholds.default == 0
+            || thresholds.max() > weight
+        {
+            return Err(Error::InvalidThreshold);
         }
 
         env.storage()
             .instance()
-            .set::<Symbol, i32>(&ADMIN_SIGNER_COUNT, &count);
+            .set::<DataKey, Thresholds>(&DataKey::Thresholds, &thresholds);
+
+        Self::extend_instance(&env);
+
+        env.events()
+            .publish((EVENT_TAG, symbol_short!("threshold")), thresholds);
+
+        Ok(())
     }
-}
+    // Guardians can hand the wallet over, so changing them or how they recover it waits out the timelock like any admin change
+    pub fn add_guardian(env: Env, id: Bytes, key: SignerKey) -> Result<(), Error> {
+        env.current_contract_address().require_auth();
 
-#[contracttype]
-pub struct Signature {
-    pub id: Bytes,
-    pub authenticator_data: Bytes,
-    pub client_data_json: Bytes,
-    pub signature: BytesN<64>,
-}
+        key.check()?;
 
-// TODO do we need this? I don't understand it
-#[derive(serde::Deserialize)]
-struct ClientDataJson<'a> {
-    challenge: &'a str,
-}
+        if Self::get_timelock(&env) > 0 {
+            return Self::queue(&env, AdminOp::AddGuardian(id, key));
+        }
 
-#[contractimpl]
-impl CustomAccountInterface for Contract {
-    type Error = Error;
-    type Signature = Signature;
+        Self::store_guardian(&env, id, key)?;
 
-    #[allow(non_snake_case)]
-    fn __check_auth(
-        env: Env,
-        signature_payload: Hash<32>,
-        signature: Signature,
-        auth_contexts: Vec<Context>,
-    ) -> Result<(), Error> {
-        let Signature {
-            id,
-            mut authenticator_data,
-            client_data_json,
-            signature,
-        } = signature;
+        Self::extend_instance(&env);
 
-        let max_ttl = env.storage().max_ttl();
+        Ok(())
+    }
+    pub fn remove_guardian(env: Env, id: Bytes) -> Result<(), Error> {
+        env.current_contract_address().require_auth();
 
-        let pk = match env.storage().temporary().get(&id) {
-            Some(pk) => {
-                // Error if a session signer is trying to perform protected actions
-                for context in auth_contexts.iter() {
-                    match context {
-                        Context::Contract(c) => {
-                            if c.contract == env.current_contract_address() // if we're calling self
-                                && ( // and
-                                    c.fn_name != symbol_short!("remove") // the method isn't the only potentially available self command
-                                    || ( // we're not removing ourself
-                                        c.fn_name == symbol_short!("remove") 
-                                        && Bytes::from_val(&env, &c.args.get(0).unwrap()) != id
-                                    )
-                                )
-                            {
-                                return Err(Error::NotPermitted);
-                            }
-                        }
-                        _ => {} // Don't block for example the deploying of new contracts from this contract
-                    };
-                }
+        if Self::get_timelock(&env) > 0 {
+            return Self::queue(&env, AdminOp::RemoveGuardian(id));
+        }
 
-                env.storage()
-                    .temporary()
-                    .extend_ttl(&id, max_ttl - WEEK_OF_LEDGERS, max_ttl);
+        Self::delete_guardian(&env, id)?;
 
-                pk
-            }
-            None => {
-                env.storage()
-                    .persistent()
-                    .extend_ttl(&id, max_ttl - WEEK_OF_LEDGERS, max_ttl);
+        Self::extend_instance(&env);
 
-                env.storage().persistent().get(&id).ok_or(Error::NotFound)?
-            }
-        };
+        Ok(())
+    }
+    pub fn set_recovery(env: Env, recovery: Recovery) -> Result<(), Error> {
+        env.current_contract_address().require_auth();
 
-        authenticator_data.extend_from_array(&env.crypto().sha256(&client_data_json).to_array());
+        if Self::get_timelock(&env) > 0 {
+            return Self::queue(&env, AdminOp::SetRecovery(recovery));
+        }
 
-        env.crypto()
-            .secp256r1_verify(&pk, &env.crypto().sha256(&authenticator_data), &signature);
+        Self::store_recovery(&env, recovery)?;
 
-        // Parse the client data JSON, extracting the base64 url encoded challenge.
-        let client_data_json = client_data_json.to_buffer::<1024>(); // <- TODO why 1024?
-        let client_data_json = client_data_json.as_slice();
-        let (client_data_json, _): (ClientDataJson, _) =
-            serde_json_core::de::from_slice(client_data_json).map_err(|_| Error::JsonParseError)?;
+        Self::extend_instance(&env);
 
-        // Build what the base64 url challenge is expecting.
-        let mut expected_challenge = [0u8; 43];
+        Ok(())
+    }
+    // Only a quorum of guardians can authorize this, see `__check_auth`
+    pub fn start_recovery(env: Env, id: Bytes, pk: BytesN<65>) -> Result<(), Error> {
+        env.current_contract_address().require_auth();
 
-        base64_url::encode(&mut expected_challenge, &signature_payload.to_array());
+        secp256r1::check_public_key(&pk)?;
 
-        // Check that the challenge inside the client data JSON that was signed is identical to the expected challenge.
-        // TODO is this check actually necessary or is the secp256r1_verify enough?
-        if client_data_json.challenge.as_bytes() != expected_challenge {
-            return Err(Error::ClientDataJsonChallengeIncorrect);
+        if env
+            .storage()
+            .persistent()
+            .has(&DataKey::Guardian(id.clone()))
+        {
+            return Err(Error::SignerDuplicated);
         }
 
-        env.storage()
+        let recovery = env
+            .storage()
             .instance()
-            .extend_ttl(max_ttl - WEEK_OF_LEDGERS, max_ttl);
+            .get::<DataKey, Recovery>(&DataKey::Recovery)
+            .ok_or(Error::NotFound)?;
+
+        if env.storage().instance().has(&DataKey::PendingRecovery) {
+            return Err(Error::RecoveryPending);
+        }
+
+        let execute_after = env.ledger().sequence().saturating_add(recovery.delay);
+
+        env.storage().instance().set::<DataKey, PendingRecovery>(
+            &DataKey::PendingRecovery,
+            &PendingRecovery {
+                id: id.clone(),
+                pk: pk.clone(),
+                execute_after,
+            },
+        );
+
+        Self::extend_instance(&env);
+
+        env.events().publish(
+            (EVENT_TAG, symbol_short!("rec_start"), id, pk),
+            execute_after,
+        );
+
+        Ok(())
+    }
+    // Any single admin can cancel a pending recovery, see `__check_auth`
+    pub fn cancel_recovery(env: Env) -> Result<(), Error> {
+        env.current_contract_address().require_auth();
+
+        let pending = env
+            .storage()
+            .instance()
+            .get::<DataKey, PendingRecovery>(&DataKey::PendingRecovery)
+            .ok_or(Error::NotFound)?;
+
+        env.storage().instance().remove(&DataKey::PendingRecovery);
+
+        Self::extend_instance(&env);
+
+        env.events()
+            .publish((EVENT_TAG, symbol_short!("rec_stop"), pending.id), ());
+
+        Ok(())
+    }
+    // Anyone can finish a recovery once its delay has passed
+    pub fn finish_recovery(env: Env) -> Result<(), Error> {
+        let pending = env
+            .storage()
+            .instance()
+            .get::<DataKey, PendingRecovery>(&DataKey::PendingRecovery)
+            .ok_or(Error::NotFound)?;
+
+        if env.ledger().sequence() < pending.execute_after {
+            return Err(Error::RecoveryNotReady);
+        }
+
+        env.storage().instance().remove(&DataKey::PendingRecovery);
+
+        // The recovered admin must be able to meet every threshold on its own
+        let signer = Signer::new(
+            &env,
+            SignerKey::Secp256r1(pending.pk),
+            Self::get_thresholds(&env).max(),
+        );
+
+        Self::store_signer(&env, &pending.id, &signer, true);
+
+        Self::extend_instance(&env);
+
+        env.events()
+            .publish((EVENT_TAG, symbol_short!("rec_done"), pending.id), ());
+
+        Ok(())
+    }
+    pub fn set_timelock(env: Env, delay: u32) -> Result<(), Error> {
+        env.current_contract_address().require_auth();
+
+        // Otherwise the timelock could simply be switched off first
+        if Self::get_timelock(&env) > 0 {
+            return Self::queue(&env, AdminOp::SetTimelock(delay));
+        }
+
+        Self::store_timelock(&env, delay)?;
+
+        Self::extend_instance(&env);
 
         Ok(())
     }
+    // Anyone can execute a pending operation once its delay has passed
+    pub fn execute_pending(env: Env, op_id: u32) -> Result<(), Error> {
+        let key = DataKey::Pending(op_id);
+        let pending = env
+            .storage()
+            .persistent()
+            .get::<_, PendingOp>(&key)
+            .ok_or(Error::NotFound)?;
+
+        if env.ledger().sequence() < pending.execute_after {
+            return Err(Error::TimelockNotReady);
+        }
+
+        env.storage().persistent().remove(&key);
+
+        match pending.op {
+            AdminOp::Add(id, signer, admin) => Self::store_signer(&env, &id, &signer, admin),
+            AdminOp::Remove(id) => Self::remove_signer(&env, id),
+            AdminOp::Rotate(old_id, new_id, new_key) => {
+                Self::rotate_signer(&env, old_id, new_id, new_key)?
+            }
+            AdminOp::Update(hash) => env.deployer().update_current_contract_wasm(hash),
+            AdminOp::SetTimelock(delay) => Self::store_timelock(&env, delay)?,
+            AdminOp::AddGuardian(id, key) => Self::store_guardian(&env, id, key)?,
+            AdminOp::RemoveGuardian(id) => Self::delete_guardian(&env, id)?,
+            AdminOp::SetRecovery(recovery) => Self::store_recovery(&env, recovery)?,
+        }
+
+        Self::extend_instance(&env);
+
+        env.events()
+            .publish((EVENT_TAG, symbol_short!("executed"), op_id), ());
+
+        Ok(())
+    }
+    // Any single admin can cancel a pending operation, see `__check_auth`
+    pub fn cancel_pending(env: Env, op_id: u32) -> Result<(), Error> {
+        env.current_contract_address().require_auth();
+
+        let key = DataKey::Pending(op_id);
+
+        if !env.storage().persistent().has(&key) {
+            return Err(Error::NotFound);
+        }
+
+        env.storage().persistent().remove(&key);
+
+        Self::extend_instance(&env);
+
+        env.events()
+            .publish((EVENT_TAG, symbol_short!("cancelled"), op_id), ());
+
+        Ok(())
+    }
+    fn queue(env: &Env, op: AdminOp) -> Result<(), Error> {
+        let op_id = env
+            .storage()
+            .instance()
+            .get::<DataKey, u32>(&DataKey::OpCount)
+            .unwrap_or(0);
+        let key = DataKey::Pending(op_id);
+        let execute_after = env
+            .ledger()
+            .sequence()
+            .saturating_add(Self::get_timelock(env));
+
+        let pending = PendingOp { op, execute_after };
+
+        env.storage().persistent().set(&key, &pending);
+
+        Self::extend_persistent(env, &key);
+
+        env.storage()
+            .instance()
+            .set::<DataKey, u32>(&DataKey::OpCount, &(op_id + 1));
+
+        Self::extend_instance(env);
+
+        env.events().publish(
+            (EVENT_TAG, symbol_short!("queued"), op_id),
+            (pending.op, execute_after),
+        );
+
+        Ok(())
+    }
+    fn store_timelock(env: &Env, delay: u32) -> Result<(), Error> {
+        // Recoveries must wait at least as long, see `store_recovery`
+        if let Some(recovery) = env
+            .storage()
+            .instance()
+            .get::<DataKey, Recovery>(&DataKey::Recovery)
+        {
+            if recovery.delay < delay {
+                return Err(Error::NotPermitted);
+            }
+        }
+
+        env.storage()
+            .instance()
+            .set::<DataKey, u32>(&DataKey::Timelock, &delay);
+
+        env.events()
+            .publish((EVENT_TAG, symbol_short!("timelock")), delay);
+
+        Ok(())
+    }
+    fn store_webauthn(env: &Env, rp_id_hash: Option<BytesN<32>>, user_verification: bool) {
+        match &rp_id_hash {
+            Some(rp_id_hash) => env
+                .storage()
+                .instance()
+                .set::<DataKey, BytesN<32>>(&DataKey::RpIdHash, rp_id_hash),
+            None => env.storage().instance().remove(&DataKey::RpIdHash),
+        }
+
+        env.storage()
+            .instance()
+            .set::<DataKey, bool>(&DataKey::UserVerification, &user_verification);
+
+        env.events().publish(
+            (EVENT_TAG, symbol_short!("webauthn")),
+            (rp_id_hash, user_verification),
+        );
+    }
+    fn store_guardian(env: &Env, id: Bytes, key: SignerKey) -> Result<(), Error> {
+        // Signers and guardians share their sign counts, which mustn't be reset from under a signer
+        if Self::has_signer(env, &id) {
+            return Err(Error::SignerDuplicated);
+        }
+
+        let guardian = DataKey::Guardian(id.clone());
+
+        let previous = Self::load_guardian(env, &id);
+
+        if previous.is_none() {
+            env.storage().instance().set::<DataKey, u32>(
+                &DataKey::GuardianCount,
+                &(Self::get_guardian_count(env) + 1),
+            );
+        }
+
+        // A new key comes with its own counter
+        if previous.as_ref() != Some(&key) {
+            env.storage()
+                .persistent()
+                .remove(&DataKey::SignCount(id.clone()));
+        }
+
+        env.storage().persistent().set(&guardian, &key);
+
+        Self::extend_persistent(env, &guardian);
+
+        env.events()
+            .publish((EVENT_TAG, symbol_short!("add_guard"), id, key), ());
+
+        Ok(())
+    }
+    fn delete_guardian(env: &Env, id: Bytes) -> Result<(), Error> {
+        let guardian = DataKey::Guardian(id.clone());
+
+        if env.storage().persistent().has(&guardian) {
+            let count = Self::get_guardian_count(env) - 1;
+
+            // Never leave fewer guardians than it takes to start a recovery
+            if let Some(recovery) = env
+                .storage()
+                .instance()
+                .get::<DataKey, Recovery>(&DataKey::Recovery)
+            {
+                if count < recovery.threshold {
+                    return Err(Error::InvalidThreshold);
+                }
+            }
+
+            env.storage().persistent().remove(&guardian);
+            env.storage()
+                .persistent()
+                .remove(&DataKey::SignCount(id.clone()));
+
+            env.storage()
+                .instance()
+                .set::<DataKey, u32>(&DataKey::GuardianCount, &count);
+        }
+
+        env.events()
+            .publish((EVENT_TAG, symbol_short!("rm_guard"), id), ());
+
+        Ok(())
+    }
+    fn store_recovery(env: &Env, recovery: Recovery) -> Result<(), Error> {
+        if recovery.threshold == 0 || recovery.threshold > Self::get_guardian_count(env) {
+            return Err(Error::InvalidThreshold);
+        }
+
+        // A recovery mustn't be a quicker way to install an admin than the timelock, see `store_timelock`
+        if recovery.delay < Self::get_timelock(env) {
+            return Err(Error::NotPermitted);
+        }
+
+        env.storage()
+            .instance()
+            .set::<DataKey, Recovery>(&DataKey::Recovery, &recovery);
+
+        env.events()
+            .publish((EVENT_TAG, symbol_short!("recovery")), recovery);
+
+        Ok(())
+    }
+    fn store_signer(env: &Env, id: &Bytes, signer: &Signer, admin: bool) {
+        let key = DataKey::Signer(id.clone());
+        let weight = signer.weight as i64;
+        let previous = Self::load_signer(env, id);
+        let signer = &match &previous {
+            // Re-adding keeps when the signer was first added and what restricts it, which is only ever changed through its own setter.
+            // Limits and scopes only apply to session signers, so an admin is left without any
+            Some((_, previous)) => {
+                // A new key comes with its own counter
+                if previous.key != signer.key {
+                    env.storage()
+                        .persistent()
+                        .remove(&DataKey::SignCount(id.clone()));
+                }
+
+                let (limits, scope) = if admin {
+                    (Map::new(env), Map::new(env))
+                } else {
+                    (previous.limits.clone(), previous.scope.clone())
+                };
+
+                Signer {
+                    policies: previous.policies.clone(),
+                    limits,
+                    scope,
+                    created: previous.created,
+                    ..signer.clone()
+                }
+            }
+            // Expired session signers can leave these behind
+            None => {
+                env.storage()
+                    .persistent()
+                    .remove(&DataKey::SignCount(id.clone()));
+                env.storage()
+                    .persistent()
+                    .remove(&DataKey::LastUsed(id.clone()));
+
+                signer.clone()
+            }
+        };
+
+        match previous {
+            // Re-adding an existing admin only replaces its key and weight
+            Some((true, previous)) if admin => {
+                Self::update_admin_signers(env, 0, weight - previous.weight as i64)
+            }
+            Some((true, previous)) => {
+                Self::update_admin_signers(env, -1, -(previous.weight as i64));
+
+                env.storage().persistent().remove(&key);
+            }
+            Some((false, _)) if admin => {
+                env.storage().temporary().remove(&key);
+
+                Self::update_admin_signers(env, 1, weight);
+            }
+            None if admin => Self::update_admin_signers(env, 1, weight),
+            _ => {}
+        }
+
+        Self::save_signer(env, id, signer, admin);
+
+        Self::update_signer_index(env, Some(id));
+
+        let valid_after = (signer.valid_after != 0).then_some(signer.valid_after);
+        let valid_until = (signer.valid_until != u32::MAX).then_some(signer.valid_until);
+
+        // TEMP until Zephyr fixes their event processing system to allow for bytesn arrays in the data field
+        // env.events()
+        //     .publish((EVENT_TAG, symbol_short!("add"), id), (key, admin, weight, valid_after, valid_until, label, created));
+        env.events().publish(
+            (
+                EVENT_TAG,
+                symbol_short!("add"),
+                id.clone(),
+                signer.key.clone(),
+            ),
+            (
+                admin,
+                signer.weight,
+                valid_after,
+                valid_until,
+                signer.label.clone(),
+                signer.created,
+            ),
+        );
+    }
+    fn remove_signer(env: &Env, id: Bytes) {
+        let key = DataKey::Signer(id.clone());
+        env.storage()
+            .persistent()
+            .remove(&DataKey::SignCount(id.clone()));
+        env.storage()
+            .persistent()
+            .remove(&DataKey::LastUsed(id.clone()));
+
+        match Self::load_signer(env, &id) {
+            Some((true, previous)) => {
+                Self::update_admin_signers(env, -1, -(previous.weight as i64));
+
+                env.storage().persistent().remove(&key);
+            }
+            Some((false, _)) => env.storage().temporary().remove(&key),
+            None => {}
+        }
+
+        Self::update_signer_index(env, None);
+
+        env.events()
+            .publish((EVENT_TAG, symbol_short!("remove"), id), ());
+    }
+    // Checked again when a queued rotation runs, as the signers may have changed since it was queued
+    fn check_rotation(env: &Env, old_id: &Bytes, new_id: &Bytes) -> Result<(bool, Signer), Error> {
+        let signer = Self::load_signer(env, old_id).ok_or(Error::NotFound)?;
+
+        if new_id != old_id
+            && (Self::has_signer(env, new_id)
+                || env
+                    .storage()
+                    .persistent()
+                    .has(&DataKey::Guardian(new_id.clone())))
+        {
+            return Err(Error::SignerDuplicated);
+        }
+
+        Ok(signer)
+    }
+    // Admin counts and weights are unaffected as the role and weight carry over
+    fn rotate_signer(
+        env: &Env,
+        old_id: Bytes,
+        new_id: Bytes,
+        new_key: SignerKey,
+    ) -> Result<(), Error> {
+        let (admin, mut signer) = Self::check_rotation(env, &old_id, &new_id)?;
+
+        env.storage()
+            .persistent()
+            .remove(&DataKey::SignCount(old_id.clone()));
+
+        if let Some(last_used) = env
+            .storage()
+            .persistent()
+            .get::<_, u32>(&DataKey::LastUsed(old_id.clone()))
+        {
+            let key = DataKey::LastUsed(new_id.clone());
+
+            env.storage()
+                .persistent()
+                .remove(&DataKey::LastUsed(old_id.clone()));
+            env.storage().persistent().set(&key, &last_used);
+
+            Self::extend_persistent(env, &key);
+        }
+
+        let key = DataKey::Signer(old_id.clone());
+
+        if admin {
+            env.storage().persistent().remove(&key);
+        } else {
+            env.storage().temporary().remove(&key);
+        }
+
+        // Carry over what's been spent so rotating doesn't reset any limits
+        for (token, limit) in signer.limits.items() {
+            let old_key = DataKey::Spent(old_id.clone(), token.clone());
+
+            if let Some(spends) = env.storage().temporary().get::<_, Vec<Spend>>(&old_key) {
+                let new_key = DataKey::Spent(new_id.clone(), token);
+                let window = limit.window.min(env.storage().max_ttl());
+
+                env.storage().temporary().remove(&old_key);
+                env.storage().temporary().set(&new_key, &spends);
+                env.storage()
+                    .temporary()
+                    .extend_ttl(&new_key, window, window);
+            }
+        }
+
+        signer.key = new_key.clone();
+
+        Self::save_signer(env, &new_id, &signer, admin);
+
+        Self::update_signer_index(env, Some(&new_id));
+
+        // Like `add` the key goes in the topics, see `store_signer`
+        env.events().publish(
+            (EVENT_TAG, symbol_short!("rotate"), new_id, new_key),
+            old_id,
+        );
+
+        Ok(())
+    }
+    // Keeps every signer's id enumerable for `list_signers`, dropping any that are gone, including expired session signers
+    fn update_signer_index(env: &Env, new_id: Option<&Bytes>) {
+        let mut ids: Vec<Bytes> = Vec::new(env);
+
+        for id in env
+            .storage()
+            .persistent()
+            .get::<DataKey, Vec<Bytes>>(&DataKey::Signers)
+            .unwrap_or(Vec::new(env))
+            .items()
+        {
+            if Self::has_signer(env, &id) {
+                ids.push_back(id);
+            }
+        }
+
+        if let Some(new_id) = new_id {
+            if !ids.contains(new_id) {
+                ids.push_back(new_id.clone());
+            }
+        }
+
+        env.storage()
+            .persistent()
+            .set::<DataKey, Vec<Bytes>>(&DataKey::Signers, &ids);
+
+        Self::extend_persistent(env, &DataKey::Signers);
+    }
+    // The legacy count drifted, e.g. re-adding an admin counted it twice, so admins are instead counted as they're migrated, see `migrate_signer`
+    fn migrate_instance(env: &Env) {
+        if env.storage().instance().has(&LEGACY_ADMIN_SIGNER_COUNT) {
+            env.storage().instance().remove(&LEGACY_ADMIN_SIGNER_COUNT);
+            env.storage()
+                .instance()
+                .set::<DataKey, bool>(&DataKey::LegacySigners, &true);
+
+            // Still marks the wallet as initialized, see `init`
+            if !env.storage().instance().has(&DataKey::AdminCount) {
+                env.storage()
+                    .instance()
+                    .set::<DataKey, i32>(&DataKey::AdminCount, &0);
+                env.storage()
+                    .instance()
+                    .set::<DataKey, u32>(&DataKey::AdminWeight, &0);
+            }
+        }
+    }
+    // Recounts the admins from storage, for wallets migrated while the legacy count was still carried over
+    fn reconcile_admin_signers(env: &Env) -> Result<(), Error> {
+        let mut count: i32 = 0;
+        let mut weight: u32 = 0;
+
+        for id in env
+            .storage()
+            .persistent()
+            .get::<DataKey, Vec<Bytes>>(&DataKey::Signers)
+            .unwrap_or(Vec::new(env))
+            .items()
+        {
+            if let Some((true, signer)) = Self::load_signer(env, &id) {
+                count += 1;
+                weight = weight.saturating_add(signer.weight);
+            }
+        }
+
+        // Thresholds set against the drifted weight have to be lowered first, like with `update_admin_signers`
+        if count == 0 || weight < Self::get_thresholds(env).max() {
+            return Err(Error::NotPermitted);
+        }
+
+        env.storage()
+            .instance()
+            .set::<DataKey, i32>(&DataKey::AdminCount, &count);
+        env.storage()
+            .instance()
+            .set::<DataKey, u32>(&DataKey::AdminWeight, &weight);
+
+        env.events()
+            .publish((EVENT_TAG, symbol_short!("reconcile")), (count, weight));
+
+        Ok(())
+    }
+    // Returns false if there was nothing to migrate.
+    // Only legacy wallets still being migrated look for raw ids, sparing everyone else two reads per signer on every call
+    fn migrate_signer(env: &Env, id: &Bytes) -> bool {
+        Self::migrate_instance(env);
+
+        if !env.storage().instance().has(&DataKey::LegacySigners) {
+            return false;
+        }
+
+        let (admin, pk) = if let Some(pk) = env.storage().temporary().get::<Bytes, BytesN<65>>(id) {
+            env.storage().temporary().remove(id);
+
+            (false, pk)
+        } else if let Some(pk) = env.storage().persistent().get::<Bytes, BytesN<65>>(id) {
+            env.storage().persistent().remove(id);
+
+            (true, pk)
+        } else {
+            return false;
+        };
+
+        // Keys the host can't use are dropped, they could never have signed anyway
+        if secp256r1::check_public_key(&pk).is_err() {
+            return false;
+        }
+
+        let signer = Signer::new(env, SignerKey::Secp256r1(pk), 1);
+
+        // Only ever adds weight, so unlike `update_admin_signers` there's no threshold to keep above
+        if admin {
+            env.storage().instance().set::<DataKey, i32>(
+                &DataKey::AdminCount,
+                &(Self::admin_count(env.clone()) as i32 + 1),
+            );
+            env.storage().instance().set::<DataKey, u32>(
+                &DataKey::AdminWeight,
+                &Self::get_admin_weight(env).saturating_add(1),
+            );
+        }
+
+        Self::save_signer(env, id, &signer, admin);
+
+        Self::update_signer_index(env, Some(id));
+
+        env.events().publish(
+            (EVENT_TAG, symbol_short!("migrate"), id.clone(), signer.key),
+            admin,
+        );
+
+        true
+    }
+    fn update_admin_signers(env: &Env, count: i32, weight: i64) {
+        let count = env
+            .storage()
+            .instance()
+            .get::<DataKey, i32>(&DataKey::AdminCount)
+            .unwrap_or(0)
+            + count;
+        let weight = Self::get_admin_weight(env) as i64 + weight;
+
+        // Never drop below the highest threshold, or some actions would become impossible to authorize
+        if count <= 0 || weight < Self::get_thresholds(env).max() as i64 {
+            panic_with_error!(env, Error::NotPermitted)
+        }
+
+        // Queued adds are only checked against the weight when they were queued, see `add`
+        if weight > u32::MAX as i64 {
+            panic_with_error!(env, Error::InvalidWeight)
+        }
+
+        env.storage()
+            .instance()
+            .set::<DataKey, i32>(&DataKey::AdminCount, &count);
+        env.storage()
+            .instance()
+            .set::<DataKey, u32>(&DataKey::AdminWeight, &(weight as u32));
+    }
+    fn check_signatures(
+        env: &Env,
+        hash: &BytesN<32>,
+        signatures: &Vec<Signature>,
+    ) -> Result<(), Error> {
+        let mut ids: Vec<Bytes> = Vec::new(env);
+        let mut weight: u32 = 0;
+
+        for signature in signatures.items() {
+            let id = signature.id().clone();
+
+            // Each signature must come from a different signer
+            if ids.contains(&id) {
+                return Err(Error::SignerDuplicated);
+            }
+
+            ids.push_back(id);
+
+            weight = weight
+                .checked_add(Self::signature_weight(env, hash, &signature)?)
+                .ok_or(Error::InvalidWeight)?;
+        }
+
+        if weight < Self::get_thresholds(env).default {
+            return Err(Error::ThresholdNotMet);
+        }
+
+        Ok(())
+    }
+    // Unlike `__check_auth` nothing is written, not even the sign count
+    fn signature_weight(env: &Env, hash: &BytesN<32>, signature: &Signature) -> Result<u32, Error> {
+        let (admin, signer) = Self::load_signer(env, signature.id()).ok_or(Error::NotFound)?;
+
+        signer.check_validity(env)?;
+
+        // Address signers can only ever approve through an auth entry of their own
+        if matches!(signer.key, SignerKey::Address(_))
+            || matches!(signature, Signature::Delegated(_))
+            || !signer.policies.is_empty()
+            || !signer.scope.is_empty()
+            || !signer.limits.is_empty()
+        {
+            return Err(Error::NotPermitted);
+        }
+
+        verify_signature(env, hash, signature, signer.key, admin, false)?;
+
+        Ok(signer.weight)
+    }
+    // Admins live in persistent storage, session signers in temporary storage
+    fn load_signer(env: &Env, id: &Bytes) -> Option<(bool, Signer)> {
+        let key = DataKey::Signer(id.clone());
+
+        match env.storage().temporary().get::<_, Signer>(&key) {
+            Some(signer) => Some((false, signer)),
+            None => env
+                .storage()
+                .persistent()
+                .get::<_, Signer>(&key)
+                .map(|signer| (true, signer)),
+        }
+    }
+    // Read as a `Val` so guardians share the `SignerKey` conversion signers already need rather than inlining another copy
+    fn load_guardian(env: &Env, id: &Bytes) -> Option<SignerKey> {
+        env.storage()
+            .persistent()
+            .get::<_, Val>(&DataKey::Guardian(id.clone()))
+            .map(|key| key.try_into_val(env).unwrap_optimized())
+    }
+    fn has_signer(env: &Env, id: &Bytes) -> bool {
+        let key = DataKey::Signer(id.clone());
+
+        env.storage().temporary().has(&key) || env.storage().persistent().has(&key)
+    }
+    fn save_signer(env: &Env, id: &Bytes, signer: &Signer, admin: bool) {
+        let key = DataKey::Signer(id.clone());
+
+        if admin {
+            env.storage().persistent().set(&key, signer);
+        } else {
+            env.storage().temporary().set(&key, signer);
+        }
+
+        Self::extend_signer(env, id, signer, admin);
+    }
+    fn extend_signer(env: &Env, id: &Bytes, signer: &Signer, admin: bool) {
+        let key = DataKey::Signer(id.clone());
+
+        if admin {
+            Self::extend_persistent(env, &key);
+        } else {
+            let ttl = signer.ttl(env);
+
+            env.storage().temporary().extend_ttl(
+                &key,
+                ttl.min(env.storage().max_ttl() - WEEK_OF_LEDGERS),
+                ttl,
+            );
+        }
+    }
+    fn extend_persistent(env: &Env, key: &DataKey) {
+        let max_ttl = env.storage().max_ttl();
+
+        env.storage()
+            .persistent()
+            .extend_ttl(key, max_ttl - WEEK_OF_LEDGERS, max_ttl);
+    }
+    fn extend_instance(env: &Env) {
+        let max_ttl = env.storage().max_ttl();
+
+        env.storage()
+            .instance()
+            .extend_ttl(max_ttl - WEEK_OF_LEDGERS, max_ttl);
+    }
+    fn get_admin_weight(env: &Env) -> u32 {
+        env.storage()
+            .instance()
+            .get::<DataKey, u32>(&DataKey::AdminWeight)
+            .unwrap_or(0)
+    }
+    fn get_timelock(env: &Env) -> u32 {
+        env.storage()
+            .instance()
+            .get::<DataKey, u32>(&DataKey::Timelock)
+            .unwrap_or(0)
+    }
+    fn get_guardian_count(env: &Env) -> u32 {
+        env.storage()
+            .instance()
+            .get::<DataKey, u32>(&DataKey::GuardianCount)
+            .unwrap_or(0)
+    }
+    fn get_thresholds(env: &Env) -> Thresholds {
+        env.storage()
+            .instance()
+            .get::<DataKey, Thresholds>(&DataKey::Thresholds)
+            .unwrap_or(Thresholds {
+                admin: 1,
+                transfer: 1,
+                default: 1,
+            })
+    }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub enum SignerKey {
+    Secp256r1(BytesN<65>),
+    Ed25519(BytesN<32>),
+    Address(Address),
+}
+
+impl SignerKey {
+    // Catch keys `__check_auth` could never verify against before they're stored
+    fn check(&self) -> Result<(), Error> {
+        match self {
+            SignerKey::Secp256r1(pk) => secp256r1::check_public_key(pk),
+            _ => Ok(()),
+        }
+    }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Signer {
+    pub key: SignerKey,
+    pub weight: u32,
+    pub policies: Vec<Address>,
+    pub limits: Map<Address, SpendLimit>, // keyed by token, session signers only
+    pub scope: Map<Address, Vec<Symbol>>, // allowed functions (any if empty) by contract, session signers only
+    pub valid_after: u32, // first ledger the signer may be used, 0 if unset, session signers only
+    pub valid_until: u32, // last ledger the signer may be used, u32::MAX if unset, session signers only
+    pub label: String,    // e.g. the device the passkey lives on, empty if unset
+    pub created: u32,     // ledger the signer was first added
+}
+
+impl Signer {
+    // Unrestricted and valid indefinitely, as admins always are
+    fn new(env: &Env, key: SignerKey, weight: u32) -> Self {
+        Signer {
+            key,
+            weight,
+            policies: Vec::new(env),
+            limits: Map::new(env),
+            scope: Map::new(env),
+            valid_after: 0,
+            valid_until: u32::MAX,
+            label: String::from_str(env, ""),
+            created: env.ledger().sequence(),
+        }
+    }
+    fn check_validity(&self, env: &Env) -> Result<(), Error> {
+        let sequence = env.ledger().sequence();
+
+        if sequence < self.valid_after {
+            return Err(Error::SignerNotYetValid);
+        }
+
+        if sequence > self.valid_until {
+            return Err(Error::SignerExpired);
+        }
+
+        Ok(())
+    }
+    // Session signers live in temporary storage, which must never outlive their `valid_until`
+    fn ttl(&self, env: &Env) -> u32 {
+        env.storage()
+            .max_ttl()
+            .min(self.valid_until.saturating_sub(env.ledger().sequence()))
+    }
+}
+
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SignerInfo {
+    pub id: Bytes,
+    pub admin: bool,
+    pub signer: Signer,
+    pub last_used: u32, // 0 if never used
+}
+
+// Caps what a session signer may spend of a token over any `window` ledgers
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct SpendLimit {
+    pub limit: i128,
+    pub window: u32,
+}
+
+#[contracttype(export = false)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Spend {
+    pub ledger: u32,
+    pub amount: i128,
+}
+
+// Policy contracts get the final say over every auth a signer they're attached to takes part in, all of them must approve
+#[contractclient(name = "PolicyClient")]
+pub trait PolicyInterface {
+    fn policy(env: Env, signer_id: Bytes, auth_contexts: Vec<Context>) -> bool;
+}
+
+// Required signature weight per category of `Context`
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Thresholds {
+    pub admin: u32,    // calls to this wallet, e.g. `add`, `remove` and `update`
+    pub transfer: u32, // token transfers, see `TRANSFER_FNS`
+    pub default: u32,  // everything else
+}
+
+impl Thresholds {
+    fn max(&self) -> u32 {
+        self.admin.max(self.transfer).max(self.default)
+    }
+}
+
+// Guardians can jointly hand the wallet over to a new admin passkey, which existing admins get `delay` ledgers to veto
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Recovery {
+    pub threshold: u32, // guardian signatures needed to start a recovery
+    pub delay: u32,     // ledgers between starting and finishing a recovery
+}
+
+#[contracttype(export = false)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingRecovery {
+    pub id: Bytes,
+    pub pk: BytesN<65>,
+    pub execute_after: u32,
+}
+
+// Admin changes held back by a timelock, see `set_timelock`
+#[contracttype(export = false)]
+#[derive(Clone, Debug, PartialEq)]
+#[allow(clippy::large_enum_variant)] // only host object handles on-chain
+pub enum AdminOp {
+    Add(Bytes, Signer, bool),
+    Remove(Bytes),
+    Rotate(Bytes, Bytes, SignerKey),
+    Update(BytesN<32>),
+    SetTimelock(u32),
+    AddGuardian(Bytes, SignerKey),
+    RemoveGuardian(Bytes),
+    SetRecovery(Recovery),
+}
+
+#[contracttype(export = false)]
+#[derive(Clone, Debug, PartialEq)]
+pub struct PendingOp {
+    pub op: AdminOp,
+    pub execute_after: u32,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct Secp256r1Signature {
+    pub id: Bytes,
+    pub authenticator_data: Bytes,
+    pub client_data_json: Bytes,
+    pub signature: Bytes, // DER as returned by browsers, or compact `r || s`
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub struct Ed25519Signature {
+    pub id: Bytes,
+    pub signature: BytesN<64>,
+}
+
+// One passkey prompt for several auth entries, the passkey signs the Merkle root of all their signature payloads, see `merkle_root`
+#[contracttype]
+#[derive(Clone)]
+pub struct Secp256r1BatchSignature {
+    pub signature: Secp256r1Signature, // with the root as its challenge
+    pub proof: Vec<BytesN<32>>, // siblings from this entry's signature payload up to the root
+}
+
+// What a passkey signs off on to let an ephemeral key act for it, see `DelegatedSignature`
+#[contracttype]
+#[derive(Clone, Debug, PartialEq)]
+pub struct Delegation {
+    pub key: BytesN<32>,                  // the ephemeral ed25519 public key
+    pub epoch: u32,                       // the signer's, see `revoke_delegations`
+    pub valid_after: u32, // first ledger the key may be used, usually the one it's issued in
+    pub valid_until: u32, // last ledger the key may be used, at most a week after `valid_after`
+    pub scope: Map<Address, Vec<Symbol>>, // as for session signers, any if empty
+}
+
+impl Delegation {
+    // Bound to this wallet so the certificate can't be replayed on another the same passkey signs for
+    fn challenge(&self, env: &Env) -> BytesN<32> {
+        env.crypto()
+            .sha256(&(env.current_contract_address(), self.clone()).to_xdr(env))
+            .to_bytes()
+    }
+}
+
+// Session keys with no on-chain setup, the passkey certifies a `Delegation` once, off-chain, and the ephemeral key then signs each payload.
+// A certificate is meant to be used many times over so its sign count isn't checked, it only lapses with `valid_until`, its epoch or its passkey
+#[contracttype]
+#[derive(Clone)]
+pub struct DelegatedSignature {
+    pub delegation: Delegation,
+    pub certificate: Secp256r1Signature, // the passkey's, with the delegation's challenge
+    pub signature: BytesN<64>,           // the ephemeral key's, over the signature payload
+}
+
+// Address signers authorize through their own auth entry, so the wallet only needs to know which signer is meant
+#[contracttype]
+#[derive(Clone)]
+pub struct AddressSignature {
+    pub id: Bytes,
+}
+
+#[contracttype]
+#[derive(Clone)]
+pub enum Signature {
+    Secp256r1(Secp256r1Signature),
+    Ed25519(Ed25519Signature),
+    Address(AddressSignature),
+    Secp256r1Batch(Secp256r1BatchSignature),
+    Delegated(DelegatedSignature),
+}
+
+impl Signature {
+    fn id(&self) -> &Bytes {
+        match self {
+            Signature::Secp256r1(signature) => &signature.id,
+            Signature::Secp256r1Batch(signature) => &signature.signature.id,
+            Signature::Delegated(signature) => &signature.certificate.id,
+            Signature::Ed25519(signature) => &signature.id,
+            Signature::Address(signature) => &signature.id,
+        }
+    }
+}
+
+#[contractimpl]
+impl CustomAccountInterface for Contract {
+    type Error = Error;
+    type Signature = Vec<Signature>;
+
+    #[allow(non_snake_case)]
+    fn __check_auth(
+        env: Env,
+        signature_payload: Hash<32>,
+        signatures: Vec<Signature>,
+        auth_contexts: Vec<Context>,
+    ) -> Result<(), Error> {
+        let payload = signature_payload.to_bytes();
+        let auth_contexts = expand_exec(&env, &auth_contexts)?;
+
+        Self::migrate_instance(&env);
+
+        let veto = !auth_contexts.is_empty()
+            && auth_contexts.items().all(|context| is_veto(&env, &context));
+        let mut ids: Vec<Bytes> = Vec::new(&env);
+        let mut session_ids: Vec<Bytes> = Vec::new(&env);
+        let mut admin_weight: u32 = 0;
+        let mut weight: u32 = 0;
+        let mut guardians: u32 = 0;
+
+        for signature in signatures.items() {
+            let id = signature.id().clone();
+
+            // Each signature must come from a different signer
+            if ids.contains(&id) {
+                return Err(Error::SignerDuplicated);
+            }
+
+            ids.push_back(id.clone());
+
+            // Legacy signers must still be able to authorize, not least the `migrate` call itself
+            Self::migrate_signer(&env, &id);
+
+            let delegated = match &signature {
+                Signature::Delegated(signature) => {
+                    check_delegation(&env, &id, &signature.delegation, &auth_contexts)?;
+
+                    true
+                }
+                _ => false,
+            };
+
+            let Some((admin, signer)) = Self::load_signer(&env, &id) else {
+                let guardian = DataKey::Guardian(id.clone());
+                let key = Self::load_guardian(&env, &id).ok_or(Error::NotFound)?;
+
+                check_guardian(&env, &auth_contexts)?;
+
+                Self::extend_persistent(&env, &guardian);
+
+                verify_signature(&env, &payload, &signature, key, true, false)?;
+                update_sign_count(&env, &payload, &signature)?;
+
+                // Guardians carry no weight, they only count towards starting a recovery
+                guardians += 1;
+
+                continue;
+            };
+
+            Self::extend_signer(&env, &id, &signer, admin);
+
+            signer.check_validity(&env)?;
+
+            if !admin {
+                check_session(&env, &id, &signer, &auth_contexts)?;
+
+                session_ids.push_back(id.clone());
+            } else if !delegated {
+                // A delegated key never acts as an admin
+                admin_weight = admin_weight
+                    .checked_add(signer.weight)
+                    .ok_or(Error::InvalidWeight)?;
+            }
+
+            weight = weight
+                .checked_add(signer.weight)
+                .ok_or(Error::InvalidWeight)?;
+
+            // Like the WebAuthn settings, policies are set right away, so they mustn't stand in the way of a veto
+            if !veto {
+                for policy in signer.policies.items() {
+                    match PolicyClient::new(&env, &policy).try_policy(&id, &auth_contexts) {
+                        Ok(Ok(true)) => {}
+                        _ => return Err(Error::PolicyRejected),
+                    }
+                }
+            }
+
+            verify_signature(&env, &payload, &signature, signer.key, admin, veto)?;
+            update_sign_count(&env, &payload, &signature)?;
+
+            let last_used = DataKey::LastUsed(id.clone());
+            let ledger = env.ledger().sequence();
+
+            env.storage().persistent().set(&last_used, &ledger);
+
+            Self::extend_persistent(&env, &last_used);
+
+            env.events()
+                .publish((EVENT_TAG, symbol_short!("used"), id), ledger);
+        }
+
+        if ids.is_empty() {
+            return Err(Error::NotFound);
+        }
+
+        let thresholds = Self::get_thresholds(&env);
+
+        // Every context must meet the threshold of its category
+        for context in auth_contexts.items() {
+            let (threshold, weight) = match &context {
+                Context::Contract(c) if c.contract == env.current_contract_address() => {
+                    // A session signer removing itself needs no one else's approval
+                    if c.fn_name == symbol_short!("remove")
+                        && session_ids.contains(Bytes::from_val(&env, &c.args.get(0).unwrap()))
+                    {
+                        continue;
+                    }
+
+                    if c.fn_name == Symbol::new(&env, "start_recovery") {
+                        let recovery = env
+                            .storage()
+                            .instance()
+                            .get::<DataKey, Recovery>(&DataKey::Recovery)
+                            .ok_or(Error::NotFound)?;
+
+                        (recovery.threshold, guardians)
+                    } else if c.fn_name == Symbol::new(&env, "cancel_recovery")
+                        || c.fn_name == Symbol::new(&env, "cancel_pending")
+                    {
+                        (1, admin_weight)
+                    } else {
+                        (thresholds.admin, admin_weight)
+                    }
+                }
+                Context::Contract(c) if is_transfer(&env, &c.fn_name) => {
+                    (thresholds.transfer, weight)
+                }
+                _ => (thresholds.default, weight),
+            };
+
+            if weight < threshold {
+                return Err(Error::ThresholdNotMet);
+            }
+        }
+
+        Self::extend_instance(&env);
+
+        Ok(())
+    }
+}
+
+fn verify_secp256r1(
+    env: &Env,
+    signature_payload: &BytesN<32>,
+    signature: &Secp256r1Signature,
+    pk: &BytesN<65>,
+    admin: bool,
+    veto: bool,
+) -> Result<(), Error> {
+    let Secp256r1Signature {
+        authenticator_data,
+        client_data_json,
+        signature,
+        ..
+    } = signature;
+    let mut authenticator_data = authenticator_data.clone();
+
+    // rpIdHash (32 bytes), flags (1 byte), signCount (4 bytes), ...
+    if authenticator_data.len() < 37 {
+        return Err(Error::AuthenticatorDataInvalid);
+    }
+
+    // Assertions made for any other relying party mustn't be accepted.
+    // Vetoes skip this and the other WebAuthn settings, which a rogue admin could have changed to lock the others out, see `is_veto`
+    if let Some(rp_id_hash) = env
+        .storage()
+        .instance()
+        .get::<DataKey, BytesN<32>>(&DataKey::RpIdHash)
+    {
+        if !veto && authenticator_data.slice(..32) != Bytes::from(rp_id_hash) {
+            return Err(Error::RpIdHashMismatch);
+        }
+    }
+
+    // Admins must always prove presence, and verification if the wallet asks for it
+    if admin {
+        let flags = authenticator_data.get(32).unwrap_or(0);
+
+        if flags & FLAG_USER_PRESENT == 0 {
+            return Err(Error::UserPresenceRequired);
+        }
+
+        if flags & FLAG_USER_VERIFIED == 0
+            && !veto
+            && env
+                .storage()
+                .instance()
+                .get::<DataKey, bool>(&DataKey::UserVerification)
+                .unwrap_or(false)
+        {
+            return Err(Error::UserVerificationRequired);
+        }
+    }
+
+    authenticator_data.extend_from_array(&env.crypto().sha256(client_data_json).to_array());
+
+    let signature = secp256r1::parse_signature(env, signature)?;
+
+    // Stored keys have all been checked already, see `SignerKey::check` and `migrate_signer`
+    env.crypto()
+        .secp256r1_verify(pk, &env.crypto().sha256(&authenticator_data), &signature);
+
+    // Parse the client data JSON, extracting the base64 url encoded challenge.
+    let client_data_json = client_data_json::parse(client_data_json)?;
+
+    // Build what the base64 url challenge is expecting.
+    let mut expected_challenge = [0u8; 43];
+
+    base64_url::encode(&mut expected_challenge, &signature_payload.to_array());
+
+    // Check that the challenge inside the client data JSON that was signed is identical to the expected challenge.
+    // TODO is this check actually necessary or is the secp256r1_verify enough?
+    if client_data_json.challenge != Bytes::from_array(env, &expected_challenge) {
+        return Err(Error::ClientDataJsonChallengeIncorrect);
+    }
+
+    // Registrations (`webauthn.create`) are never valid assertions
+    if client_data_json.type_ != Bytes::from_slice(env, b"webauthn.get") {
+        return Err(Error::ClientDataJsonTypeIncorrect);
+    }
+
+    if veto {
+        return Ok(());
+    }
+
+    // Without an allowlist any origin may drive the wallet
+    let origins = env
+        .storage()
+        .instance()
+        .get::<DataKey, Vec<Bytes>>(&DataKey::Origins)
+        .unwrap_or(Vec::new(env));
+
+    if !origins.is_empty() && !origins.contains(client_data_json.origin) {
+        return Err(Error::ClientDataJsonOriginNotAllowed);
+    }
+
+    if client_data_json.cross_origin
+        && !env
+            .storage()
+            .instance()
+            .get::<DataKey, bool>(&DataKey::CrossOrigin)
+            .unwrap_or(false)
+    {
+        return Err(Error::ClientDataJsonCrossOrigin);
+    }
+
+    Ok(())
+}
+
+// A counter that doesn't go up means the credential may have been cloned, authenticators that don't count always report 0
+fn update_sign_count(
+    env: &Env,
+    signature_payload: &BytesN<32>,
+    signature: &Signature,
+) -> Result<(), Error> {
+    let (signature, root) = match signature {
+        Signature::Secp256r1(signature) => (signature, None),
+        Signature::Secp256r1Batch(Secp256r1BatchSignature { signature, proof }) => {
+            (signature, Some(merkle_root(env, signature_payload, proof)))
+        }
+        _ => return Ok(()),
+    };
+
+    // Already checked to be long enough, see `verify_secp256r1`
+    let mut sign_count = [0u8; 4];
+
+    signature
+        .authenticator_data
+        .slice(33..37)
+        .copy_into_slice(&mut sign_count);
+
+    let sign_count = u32::from_be_bytes(sign_count);
+    let key = DataKey::SignCount(signature.id.clone());
+    let last_sign_count = env.storage().persistent().get::<_, u32>(&key).unwrap_or(0);
+
+    if sign_count != 0 || last_sign_count != 0 {
+        let root_key = DataKey::SignRoot(signature.id.clone());
+
+        // Every auth entry a batch covers carries the same assertion, so its counter repeats for as long as the root does
+        if sign_count == last_sign_count
+            && root.is_some()
+            && env.storage().temporary().get::<_, BytesN<32>>(&root_key) == root
+        {
+            return Ok(());
+        }
+
+        if sign_count <= last_sign_count {
+            return Err(Error::SignCountInvalid);
+        }
+
+        env.storage().persistent().set(&key, &sign_count);
+
+        Contract::extend_persistent(env, &key);
+
+        // Only needed for the rest of the batch's auth entries, which all come in the same transaction
+        if let Some(root) = root {
+            env.storage().temporary().set(&root_key, &root);
+        }
+    }
+
+    Ok(())
+}
+
+// Hashes up from a signature payload through its proof, sorting each pair first so proofs needn't say which side siblings are on
+fn merkle_root(env: &Env, leaf: &BytesN<32>, proof: &Vec<BytesN<32>>) -> BytesN<32> {
+    let mut node = leaf.clone();
+
+    for sibling in proof.items() {
+        let (left, right) = if node < sibling {
+            (node, sibling)
+        } else {
+            (sibling, node)
+        };
+        let mut pair: Bytes = left.into();
+
+        pair.append(&right.into());
+        node = env.crypto().sha256(&pair).to_bytes();
+    }
+
+    node
+}
+
+fn verify_signature(
+    env: &Env,
+    signature_payload: &BytesN<32>,
+    signature: &Signature,
+    key: SignerKey,
+    admin: bool,
+    veto: bool,
+) -> Result<(), Error> {
+    match (signature, key) {
+        (Signature::Secp256r1(signature), SignerKey::Secp256r1(pk)) => {
+            verify_secp256r1(env, signature_payload, signature, &pk, admin, veto)
+        }
+        (
+            Signature::Delegated(DelegatedSignature {
+                delegation,
+                certificate,
+                signature,
+            }),
+            SignerKey::Secp256r1(pk),
+        ) => {
+            verify_secp256r1(
+                env,
+                &delegation.challenge(env),
+                certificate,
+                &pk,
+                admin,
+                veto,
+            )?;
+
+            env.crypto().ed25519_verify(
+                &delegation.key,
+                &signature_payload.clone().into(),
+                signature,
+            );
+
+            Ok(())
+        }
+        (
+            Signature::Secp256r1Batch(Secp256r1BatchSignature { signature, proof }),
+            SignerKey::Secp256r1(pk),
+        ) => verify_secp256r1(
+            env,
+            &merkle_root(env, signature_payload, proof),
+            signature,
+            &pk,
+            admin,
+            veto,
+        ),
+        (Signature::Ed25519(signature), SignerKey::Ed25519(pk)) => {
+            env.crypto().ed25519_verify(
+                &pk,
+                &signature_payload.clone().into(),
+                &signature.signature,
+            );
+
+            Ok(())
+        }
+        (Signature::Address(_), SignerKey::Address(address)) => {
+            address.require_auth_for_args(vec![env, signature_payload.into_val(env)]);
+
+            Ok(())
+        }
+        _ => Err(Error::SignatureKeyMismatch),
+    }
+}
+
+// The calls `exec` makes are authorized by it as their invoker, so they're swapped in for it and each checked like any other context
+fn expand_exec(env: &Env, auth_contexts: &Vec<Context>) -> Result<Vec<Context>, Error> {
+    let mut contexts: Vec<Context> = Vec::new(env);
+
+    for context in auth_contexts.items() {
+        match &context {
+            Context::Contract(c)
+                if c.contract == env.current_contract_address()
+                    && c.fn_name == symbol_short!("exec") =>
+            {
+                let calls = c
+                    .args
+                    .get(0)
+                    .and_then(|calls| {
+                        Vec::<(Address, Symbol, Vec<Val>)>::try_from_val(env, &calls).ok()
+                    })
+                    .ok_or(Error::NotPermitted)?;
+
+                for (contract, fn_name, args) in calls.items() {
+                    contexts.push_back(Context::Contract(ContractContext {
+                        contract,
+                        fn_name,
+                        args,
+                    }));
+                }
+            }
+            _ => contexts.push_back(context),
+        }
+    }
+
+    Ok(contexts)
+}
+
+// Cancelling a pending operation or recovery is how admins stop one of their own gone rogue, see `cancel_pending`
+fn is_veto(env: &Env, context: &Context) -> bool {
+    match context {
+        Context::Contract(c) => {
+            c.contract == env.current_contract_address()
+                && (c.fn_name == Symbol::new(env, "cancel_pending")
+                    || c.fn_name == Symbol::new(env, "cancel_recovery"))
+        }
+        _ => false,
+    }
+}
+
+fn is_transfer(env: &Env, fn_name: &Symbol) -> bool {
+    TRANSFER_FNS
+        .iter()
+        .any(|transfer_fn| *fn_name == Symbol::new(env, transfer_fn))
+}
+
+// Guardians may only ever sign off on starting a recovery
+fn check_guardian(env: &Env, auth_contexts: &Vec<Context>) -> Result<(), Error> {
+    for context in auth_contexts.items() {
+        match context {
+            Context::Contract(c)
+                if c.contract == env.current_contract_address()
+                    && c.fn_name == Symbol::new(env, "start_recovery") => {}
+            _ => return Err(Error::NotPermitted),
+        }
+    }
+
+    Ok(())
+}
+
+fn check_session(
+    env: &Env,
+    id: &Bytes,
+    signer: &Signer,
+    auth_contexts: &Vec<Context>,
+) -> Result<(), Error> {
+    // Error if a session signer is trying to perform protected actions
+    for context in auth_contexts.items() {
+        match &context {
+            // Calling self is limited to the one potentially available self command, removing ourself
+            Context::Contract(c) if c.contract == env.current_contract_address() => {
+                if c.fn_name != symbol_short!("remove")
+                    || Bytes::from_val(env, &c.args.get(0).unwrap()) != *id
+                {
+                    return Err(Error::NotPermitted);
+                }
+            }
+            _ => {
+                if !in_scope(&signer.scope, &context) {
+                    return Err(Error::NotPermitted);
+                }
+            }
+        };
+    }
+
+    if !signer.limits.is_empty() {
+        spend(env, id, &signer.limits, auth_contexts)?;
+    }
+
+    Ok(())
+}
+
+// Delegated keys are held to their certificate, and can never call the wallet itself, not even to remove a signer.
+// Transfers must be named in the scope outright, as a delegated key carries its passkey's full weight
+fn check_delegation(
+    env: &Env,
+    id: &Bytes,
+    delegation: &Delegation,
+    auth_contexts: &Vec<Context>,
+) -> Result<(), Error> {
+    let sequence = env.ledger().sequence();
+
+    if sequence < delegation.valid_after {
+        return Err(Error::SignerNotYetValid);
+    }
+
+    // Certificates can't be left lying around for long, whatever they say.
+    // The week runs from `valid_after`, measuring it from the current ledger would let a certificate be used for as long as it's kept renewing
+    if sequence > delegation.valid_until
+        || delegation.valid_until - delegation.valid_after > MAX_DELEGATION_LEDGERS
+    {
+        return Err(Error::SignerExpired);
+    }
+
+    if delegation.epoch
+        != env
+            .storage()
+            .persistent()
+            .get::<_, u32>(&DataKey::DelegationEpoch(id.clone()))
+            .unwrap_or(0)
+    {
+        return Err(Error::SignerExpired);
+    }
+
+    for context in auth_contexts.items() {
+        match &context {
+            Context::Contract(c) if c.contract == env.current_contract_address() => {
+                return Err(Error::NotPermitted)
+            }
+            Context::Contract(c) if is_transfer(env, &c.fn_name) => {
+                match delegation.scope.get(c.contract.clone()) {
+                    Some(fn_names) if fn_names.contains(&c.fn_name) => {}
+                    _ => return Err(Error::NotPermitted),
+                }
+            }
+            _ => {
+                if !in_scope(&delegation.scope, &context) {
+                    return Err(Error::NotPermitted);
+                }
+            }
+        }
+    }
+
+    Ok(())
+}
+
+// Scoped signers may only call what they've been allowed to, which rules out anything but contract calls, e.g. deploying
+fn in_scope(scope: &Map<Address, Vec<Symbol>>, context: &Context) -> bool {
+    if scope.is_empty() {
+        return true;
+    }
+
+    match context {
+        Context::Contract(c) => match scope.get(c.contract.clone()) {
+            Some(fn_names) => fn_names.is_empty() || fn_names.contains(&c.fn_name),
+            None => false,
+        },
+        _ => false,
+    }
+}
+
+// Records what the contexts spend of limited tokens, failing if that takes a session signer over any of its limits
+fn spend(
+    env: &Env,
+    id: &Bytes,
+    limits: &Map<Address, SpendLimit>,
+    auth_contexts: &Vec<Context>,
+) -> Result<(), Error> {
+    for context in auth_contexts.items() {
+        let Context::Contract(c) = context else {
+            continue;
+        };
+        let Some(SpendLimit { limit, window }) = limits.get(c.contract.clone()) else {
+            continue;
+        };
+
+        // `transfer(from, to, amount)`, `approve(from, spender, amount, expiration_ledger)` and `burn(from, amount)`
+        let amount_index =
+            if c.fn_name == symbol_short!("transfer") || c.fn_name == symbol_short!("approve") {
+                2
+            } else if c.fn_name == symbol_short!("burn") {
+                1
+            } else {
+                continue;
+            };
+
+        // Only spending from this wallet counts against the limit
+        match c.args.get(0).map(|from| Address::try_from_val(env, &from)) {
+            Some(Ok(from)) if from == env.current_contract_address() => {}
+            _ => continue,
+        }
+
+        let amount = c
+            .args
+            .get(amount_index)
+            .and_then(|amount| i128::try_from_val(env, &amount).ok())
+            .ok_or(Error::NotPermitted)?
+            .max(0);
+
+        let key = DataKey::Spent(id.clone(), c.contract.clone());
+        let ledger = env.ledger().sequence();
+        let mut spent: i128 = 0;
+        let mut spends: Vec<Spend> = Vec::new(env);
+
+        // Drop spends which have fallen out of the window
+        for s in env
+            .storage()
+            .temporary()
+            .get::<_, Vec<Spend>>(&key)
+            .unwrap_or(Vec::new(env))
+            .items()
+        {
+            // A window reaching past the last ledger keeps every spend
+            if s.ledger.saturating_add(window) > ledger {
+                spent = spent.saturating_add(s.amount);
+                spends.push_back(s);
+            }
+        }
+
+        spent = spent.saturating_add(amount);
+
+        if spent > limit {
+            return Err(Error::SpendLimitExceeded);
+        }
+
+        spends.push_back(Spend { ledger, amount });
+
+        let window = window.min(env.storage().max_ttl());
+
+        env.storage().temporary().set(&key, &spends);
+        env.storage().temporary().extend_ttl(&key, window, window);
+    }
+
+    Ok(())
+}
+
+// `Vec::iter` and `Map::iter` unwrap each item with `Result::unwrap`, whose panic message formatting alone takes kilobytes of wasm.
+// Items that fail to convert trap all the same
+trait Items<T> {
+    fn items(&self) -> impl Iterator<Item = T>;
+}
+
+impl<T> Items<T> for Vec<T>
+where
+    T: IntoVal<Env, Val> + TryFromVal<Env, Val> + Clone,
+    T::Error: core::fmt::Debug,
+{
+    fn items(&self) -> impl Iterator<Item = T> {
+        self.try_iter().map(UnwrapOptimized::unwrap_optimized)
+    }
+}
+
+impl<K, V> Items<(K, V)> for Map<K, V>
+where
+    K: IntoVal<Env, Val> + TryFromVal<Env, Val> + Clone,
+    V: IntoVal<Env, Val> + TryFromVal<Env, Val> + Clone,
+{
+    fn items(&self) -> impl Iterator<Item = (K, V)> {
+        self.try_iter().map(UnwrapOptimized::unwrap_optimized)
+    }
 }
@@ -0,0 +1,245 @@
+// Checks secp256r1 (P-256) public keys and signatures before they reach `secp256r1_verify`.
+//
+// The host traps on anything it can't use, so whatever can be caught here is returned as a typed `Error` instead.
+// WebAuthn signatures are ASN.1 DER encoded `SEQUENCE { r INTEGER, s INTEGER }` with no constraint on S,
+// the host however only accepts `r || s` with S in the lower half of the curve order.
+// Parsing sticks to patterns and `get` over indexing, whose panic messages alone would take kilobytes of wasm.
+
+use soroban_sdk::{Bytes, BytesN, Env};
+
+use crate::Error;
+
+// n, the order of the P-256 group
+const ORDER: [u8; 32] = [
+    0xff, 0xff, 0xff, 0xff, 0x00, 0x00, 0x00, 0x00, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xbc, 0xe6, 0xfa, 0xad, 0xa7, 0x17, 0x9e, 0x84, 0xf3, 0xb9, 0xca, 0xc2, 0xfc, 0x63, 0x25, 0x51,
+];
+
+// n / 2, the largest S that counts as low
+const HALF_ORDER: [u8; 32] = [
+    0x7f, 0xff, 0xff, 0xff, 0x80, 0x00, 0x00, 0x00, 0x7f, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff,
+    0xde, 0x73, 0x7d, 0x56, 0xd3, 0x8b, 0xcf, 0x42, 0x79, 0xdc, 0xe5, 0x61, 0x7e, 0x31, 0x92, 0xa8,
+];
+
+// p, the field prime, as little endian 64 bit limbs
+const P: [u64; 4] = [
+    0xffffffffffffffff,
+    0x00000000ffffffff,
+    0x0000000000000000,
+    0xffffffff00000001,
+];
+
+// a = -3 mod p
+const A: [u64; 4] = [
+    0xfffffffffffffffc,
+    0x00000000ffffffff,
+    0x0000000000000000,
+    0xffffffff00000001,
+];
+
+const B: [u64; 4] = [
+    0x3bce3c3e27d2604b,
+    0x651d06b0cc53b0f6,
+    0xb3ebbd55769886bc,
+    0x5ac635d8aa3a93e7,
+];
+
+// 0x30 len 0x02 len r 0x02 len s, with r and s at most 33 bytes each
+const MAX_DER_LEN: u32 = 72;
+
+// An uncompressed SEC1 point (0x04 || x || y) on the curve y^2 = x^3 + ax + b
+pub fn check_public_key(pk: &BytesN<65>) -> Result<(), Error> {
+    let pk = pk.to_array();
+
+    if pk[0] != 0x04 {
+        return Err(Error::Secp256r1PublicKeyParse);
+    }
+
+    let x = limbs(&pk[1..33]);
+    let y = limbs(&pk[33..]);
+
+    if !less_than(&x, &P) || !less_than(&y, &P) {
+        return Err(Error::Secp256r1PublicKeyParse);
+    }
+
+    let x3 = mul_mod(&mul_mod(&x, &x), &x);
+    let rhs = add_mod(&add_mod(&x3, &mul_mod(&A, &x)), &B);
+
+    if mul_mod(&y, &y) != rhs {
+        return Err(Error::Secp256r1PublicKeyParse);
+    }
+
+    Ok(())
+}
+
+pub fn parse_signature(env: &Env, signature: &Bytes) -> Result<BytesN<64>, Error> {
+    let mut compact = [0u8; 64];
+
+    // DER with two short integers can also be 64 bytes long, so only what isn't DER is read as compact
+    if parse_der(signature, &mut compact).is_err() {
+        if signature.len() != 64 {
+            return Err(Error::Secp256r1SignatureParse);
+        }
+
+        signature.copy_into_slice(&mut compact);
+    }
+
+    let mut r = [0u8; 32];
+    let mut s = [0u8; 32];
+
+    r.copy_from_slice(&compact[..32]);
+    s.copy_from_slice(&compact[32..]);
+
+    // Both must be in [1, n - 1]
+    if r == [0; 32] || s == [0; 32] || r >= ORDER || s >= ORDER {
+        return Err(Error::Secp256r1SignatureParse);
+    }
+
+    // Arrays compare lexicographically, which for big endian integers is numerically
+    if s > HALF_ORDER {
+        compact[32..].copy_from_slice(&sub(&ORDER, &s));
+    }
+
+    Ok(BytesN::from_array(env, &compact))
+}
+
+fn parse_der(signature: &Bytes, compact: &mut [u8; 64]) -> Result<(), Error> {
+    let mut der = [0u8; MAX_DER_LEN as usize];
+    let der = der
+        .get_mut(..signature.len() as usize)
+        .filter(|der| der.len() >= 8)
+        .ok_or(Error::Secp256r1SignatureParse)?;
+
+    signature.copy_into_slice(der);
+
+    let [0x30, len, rest @ ..] = der else {
+        return Err(Error::Secp256r1SignatureParse);
+    };
+
+    if *len as usize != rest.len() {
+        return Err(Error::Secp256r1SignatureParse);
+    }
+
+    let (r, s) = compact.split_at_mut(32);
+    let rest = parse_integer(rest, r)?;
+    let rest = parse_integer(rest, s)?;
+
+    if !rest.is_empty() {
+        return Err(Error::Secp256r1SignatureParse);
+    }
+
+    Ok(())
+}
+
+// Reads a positive DER INTEGER into a left padded 32 byte big endian buffer, returning what follows it
+fn parse_integer<'a>(der: &'a [u8], dst: &mut [u8]) -> Result<&'a [u8], Error> {
+    let [0x02, len, rest @ ..] = der else {
+        return Err(Error::Secp256r1SignatureParse);
+    };
+
+    let len = *len as usize;
+    let (Some(int), Some(rest)) = (rest.get(..len), rest.get(len..)) else {
+        return Err(Error::Secp256r1SignatureParse);
+    };
+
+    let int = match int {
+        [] => return Err(Error::Secp256r1SignatureParse),
+        // Negative
+        [first, ..] if first & 0x80 != 0 => return Err(Error::Secp256r1SignatureParse),
+        // A leading zero is only there to keep the high bit of the next byte from reading as a sign
+        [0, next, ..] if next & 0x80 == 0 => return Err(Error::Secp256r1SignatureParse),
+        [0, int @ ..] if !int.is_empty() => int,
+        int => int,
+    };
+
+    if int.len() > 32 {
+        return Err(Error::Secp256r1SignatureParse);
+    }
+
+    for (dst, src) in dst.iter_mut().rev().zip(int.iter().rev()) {
+        *dst = *src;
+    }
+
+    Ok(rest)
+}
+
+// a - b, where a >= b
+fn sub(a: &[u8; 32], b: &[u8; 32]) -> [u8; 32] {
+    let mut result = [0u8; 32];
+    let mut borrow = 0i16;
+
+    for i in (0..32).rev() {
+        let mut diff = a[i] as i16 - b[i] as i16 - borrow;
+
+        borrow = (diff < 0) as i16;
+        diff += borrow << 8;
+        result[i] = diff as u8;
+    }
+
+    result
+}
+
+fn limbs(be: &[u8]) -> [u64; 4] {
+    let mut limbs = [0u64; 4];
+
+    for (i, byte) in be.iter().rev().take(32).enumerate() {
+        limbs[i / 8] |= (*byte as u64) << (i % 8 * 8);
+    }
+
+    limbs
+}
+
+fn less_than(a: &[u64; 4], b: &[u64; 4]) -> bool {
+    for i in (0..4).rev() {
+        if a[i] != b[i] {
+            return a[i] < b[i];
+        }
+    }
+
+    false
+}
+
+// (a + b) mod p, where a, b < p
+fn add_mod(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mut sum = [0u64; 4];
+    let mut carry = false;
+
+    for i in 0..4 {
+        let (s, c1) = a[i].overflowing_add(b[i]);
+        let (s, c2) = s.overflowing_add(carry as u64);
+
+        sum[i] = s;
+        carry = c1 || c2;
+    }
+
+    if carry || !less_than(&sum, &P) {
+        let mut borrow = false;
+
+        for i in 0..4 {
+            let (d, b1) = sum[i].overflowing_sub(P[i]);
+            let (d, b2) = d.overflowing_sub(borrow as u64);
+
+            sum[i] = d;
+            borrow = b1 || b2;
+        }
+    }
+
+    sum
+}
+
+// (a * b) mod p, where a, b < p, by double and add so no wide reduction is needed
+fn mul_mod(a: &[u64; 4], b: &[u64; 4]) -> [u64; 4] {
+    let mut product = [0u64; 4];
+
+    for i in (0..4).rev() {
+        for bit in (0..64).rev() {
+            product = add_mod(&product, &product);
+
+            if (b[i] >> bit) & 1 == 1 {
+                product = add_mod(&product, a);
+            }
+        }
+    }
+
+    product
+}
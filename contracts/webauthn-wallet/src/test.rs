@@ -3,26 +3,156 @@
 use std::println;
 extern crate std;
 
+use ed25519_dalek::{Signer as _, SigningKey};
 use soroban_sdk::{
-    // testutils::{Address as _, BytesN as _},
-    // token, Address,
-    vec,
-    Bytes,
-    BytesN,
-    Env,
-    IntoVal,
+    auth::{Context, ContractContext},
+    map, symbol_short,
+    testutils::{storage::Temporary as _, Address as _, Ledger as _},
+    token, vec,
+    xdr::ToXdr,
+    Address, Bytes, BytesN, Env, FromVal, IntoVal, String, Symbol, Val, Vec,
 };
 
-use crate::{Contract, ContractClient, Error, Signature};
+use crate::{
+    AddressSignature, Contract, ContractClient, DataKey, DelegatedSignature, Delegation,
+    Ed25519Signature, Error, Recovery, Secp256r1BatchSignature, Secp256r1Signature, Signature,
+    Signer, SignerKey, SpendLimit, Thresholds,
+};
+
+mod policy {
+    use soroban_sdk::{auth::Context, contract, contractimpl, symbol_short, Bytes, Env, Vec};
+
+    // Allows everything but transfers
+    #[contract]
+    pub struct Policy;
+
+    #[contractimpl]
+    impl Policy {
+        pub fn policy(_env: Env, _signer_id: Bytes, auth_contexts: Vec<Context>) -> bool {
+            auth_contexts.iter().all(|context| match context {
+                Context::Contract(c) => c.fn_name != symbol_short!("transfer"),
+                _ => true,
+            })
+        }
+    }
+}
+
+mod deny_policy {
+    use soroban_sdk::{auth::Context, contract, contractimpl, Bytes, Env, Vec};
+
+    // Allows nothing at all
+    #[contract]
+    pub struct Policy;
+
+    #[contractimpl]
+    impl Policy {
+        pub fn policy(_env: Env, _signer_id: Bytes, _auth_contexts: Vec<Context>) -> bool {
+            false
+        }
+    }
+}
 
 mod factory {
     soroban_sdk::contractimport!(file = "../out/webauthn_factory.optimized.wasm");
 }
 
-// mod passkey {
-//     use soroban_sdk::auth::Context;
-//     soroban_sdk::contractimport!(file = "../out/webauthn_wallet.optimized.wasm");
-// }
+#[allow(clippy::too_many_arguments)]
+mod passkey {
+    use soroban_sdk::auth::Context;
+    soroban_sdk::contractimport!(file = "../out/webauthn_wallet.optimized.wasm");
+}
+
+const RP_ID: &str = "passkey-kit-demo.pages.dev";
+const ORIGIN: &str = "https://passkey-kit-demo.pages.dev";
+
+fn rp_id_hash(env: &Env, rp_id: &str) -> BytesN<32> {
+    env.crypto()
+        .sha256(&Bytes::from_slice(env, rp_id.as_bytes()))
+        .to_bytes()
+}
+
+// rpIdHash, flags and signCount
+fn authenticator_data(env: &Env, rp_id: &str, flags: u8, sign_count: u32) -> Bytes {
+    let mut authenticator_data: Bytes = rp_id_hash(env, rp_id).into();
+
+    authenticator_data.push_back(flags);
+    authenticator_data.extend_from_array(&sign_count.to_be_bytes());
+    authenticator_data
+}
+
+fn secp256r1_pk(env: &Env, signing_key: &p256::ecdsa::SigningKey) -> BytesN<65> {
+    BytesN::from_array(
+        env,
+        signing_key
+            .verifying_key()
+            .to_encoded_point(false)
+            .as_bytes()
+            .try_into()
+            .unwrap(),
+    )
+}
+
+fn client_data_json(
+    env: &Env,
+    type_: &str,
+    signature_payload: &BytesN<32>,
+    origin: &str,
+    cross_origin: bool,
+) -> Bytes {
+    let mut challenge = [0u8; 43];
+
+    crate::base64_url::encode(&mut challenge, &signature_payload.to_array());
+
+    let client_data_json = std::format!(
+        r#"{{"type":"{}","challenge":"{}","origin":"{}","crossOrigin":{}}}"#,
+        type_,
+        std::str::from_utf8(&challenge).unwrap(),
+        origin,
+        cross_origin
+    );
+
+    Bytes::from_slice(env, client_data_json.as_bytes())
+}
+
+// Signs `signature_payload` the way a browser would with a passkey
+fn sign_secp256r1(
+    env: &Env,
+    id: &Bytes,
+    signing_key: &p256::ecdsa::SigningKey,
+    signature_payload: &BytesN<32>,
+    authenticator_data: Bytes,
+) -> Signature {
+    sign_client_data_json(
+        env,
+        id,
+        signing_key,
+        authenticator_data,
+        client_data_json(env, "webauthn.get", signature_payload, ORIGIN, false),
+    )
+}
+
+fn sign_client_data_json(
+    env: &Env,
+    id: &Bytes,
+    signing_key: &p256::ecdsa::SigningKey,
+    authenticator_data: Bytes,
+    client_data_json: Bytes,
+) -> Signature {
+    let mut message = authenticator_data.clone();
+
+    message.extend_from_array(&env.crypto().sha256(&client_data_json).to_array());
+
+    let signature: p256::ecdsa::Signature =
+        signing_key.sign(&message.iter().collect::<std::vec::Vec<u8>>());
+    let signature = signature.normalize_s().unwrap_or(signature);
+
+    Signature::Secp256r1(Secp256r1Signature {
+        id: id.clone(),
+        authenticator_data,
+        client_data_json,
+        signature: Bytes::from_slice(env, &signature.to_bytes()),
+    })
+}
 
 #[test]
 fn test() {
@@ -58,7 +188,7 @@ fn test() {
     // let salt = env.crypto().sha256(&id);
 
     // factory_client.init(&passkkey_hash);
-    deployee_client.add(&id, &pk, &true);
+    deployee_client.init(&id, &SignerKey::Secp256r1(pk), &None);
 
     let signature_payload = BytesN::from_array(
         &env,
@@ -68,7 +198,7 @@ fn test() {
         ],
     );
 
-    let signature = Signature {
+    let signature = Signature::Secp256r1(Secp256r1Signature {
         authenticator_data: Bytes::from_array(
             &env,
             &[
@@ -97,7 +227,7 @@ fn test() {
                 102, 187, 140,
             ],
         ),
-        signature: BytesN::from_array(
+        signature: Bytes::from_array(
             &env,
             &[
                 74, 48, 29, 120, 181, 135, 255, 178, 105, 76, 82, 118, 29, 135, 193, 72, 123, 144,
@@ -106,14 +236,2856 @@ fn test() {
                 144, 227, 11, 225, 74, 254, 191, 221, 103, 86,
             ],
         ),
-    };
+    });
 
     let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
         &deployee_address,
         &signature_payload,
-        signature.into_val(&env),
+        vec![&env, signature].into_val(&env),
         &vec![&env],
     );
 
     println!("{:?}", result);
 }
+
+#[test]
+fn test_ed25519() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let signing_key = SigningKey::from_bytes(&[7; 32]);
+    let pk = BytesN::from_array(&env, &signing_key.verifying_key().to_bytes());
+    let id = Bytes::from_array(&env, &pk.to_array());
+
+    deployee_client.init(&id, &SignerKey::Ed25519(pk), &None);
+
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+
+    let signature = Signature::Ed25519(Ed25519Signature {
+        id,
+        signature: BytesN::from_array(
+            &env,
+            &signing_key.sign(&signature_payload.to_array()).to_bytes(),
+        ),
+    });
+
+    let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+        &deployee_address,
+        &signature_payload,
+        vec![&env, signature].into_val(&env),
+        &vec![&env],
+    );
+
+    assert_eq!(result, Ok(()));
+}
+
+#[test]
+fn test_address() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let signer = Address::generate(&env);
+    let id = Bytes::from_array(&env, &[1; 32]);
+
+    deployee_client.init(&id, &SignerKey::Address(signer.clone()), &None);
+
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+    let signature = Signature::Address(AddressSignature { id });
+
+    env.mock_all_auths();
+
+    let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+        &deployee_address,
+        &signature_payload,
+        vec![&env, signature].into_val(&env),
+        &vec![&env],
+    );
+
+    assert_eq!(result, Ok(()));
+    assert_eq!(env.auths()[0].0, signer);
+}
+
+#[test]
+fn test_thresholds() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    // One hardware passkey or two phone passkeys
+    let hardware = (
+        Bytes::from_array(&env, &[1; 32]),
+        SigningKey::from_bytes(&[1; 32]),
+    );
+    let phone_a = (
+        Bytes::from_array(&env, &[2; 32]),
+        SigningKey::from_bytes(&[2; 32]),
+    );
+    let phone_b = (
+        Bytes::from_array(&env, &[3; 32]),
+        SigningKey::from_bytes(&[3; 32]),
+    );
+
+    env.mock_all_auths();
+
+    for ((id, signing_key), weight) in [(&hardware, 2), (&phone_a, 1), (&phone_b, 1)] {
+        deployee_client.add(
+            id,
+            &SignerKey::Ed25519(BytesN::from_array(
+                &env,
+                &signing_key.verifying_key().to_bytes(),
+            )),
+            &true,
+            &weight,
+            &None,
+            &None,
+            &None,
+        );
+    }
+
+    assert_eq!(
+        deployee_client.try_set_thresholds(&Thresholds {
+            admin: 5,
+            transfer: 1,
+            default: 1,
+        }),
+        Err(Ok(Error::InvalidThreshold))
+    );
+
+    // The admins' combined weight has to fit in a `u32`
+    assert_eq!(
+        deployee_client.try_add(
+            &phone_a.0,
+            &SignerKey::Ed25519(BytesN::from_array(
+                &env,
+                &phone_a.1.verifying_key().to_bytes(),
+            )),
+            &true,
+            &(u32::MAX - 2),
+            &None,
+            &None,
+            &None,
+        ),
+        Err(Ok(Error::InvalidWeight))
+    );
+
+    deployee_client.set_thresholds(&Thresholds {
+        admin: 2,
+        transfer: 2,
+        default: 1,
+    });
+
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+    let sign = |signers: &[&(Bytes, SigningKey)]| {
+        let mut signatures = vec![&env];
+
+        for (id, signing_key) in signers {
+            signatures.push_back(Signature::Ed25519(Ed25519Signature {
+                id: id.clone(),
+                signature: BytesN::from_array(
+                    &env,
+                    &signing_key.sign(&signature_payload.to_array()).to_bytes(),
+                ),
+            }));
+        }
+
+        signatures
+    };
+    let check_auth = |signatures: soroban_sdk::Vec<Signature>, contract: &Address, fn_name| {
+        let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+            &deployee_address,
+            &signature_payload,
+            signatures.into_val(&env),
+            &vec![
+                &env,
+                Context::Contract(ContractContext {
+                    contract: contract.clone(),
+                    fn_name,
+                    args: vec![&env],
+                }),
+            ],
+        );
+
+        result
+    };
+    let token = Address::generate(&env);
+
+    assert_eq!(
+        check_auth(
+            sign(&[&phone_a]),
+            &deployee_address,
+            symbol_short!("update")
+        ),
+        Err(Ok(Error::ThresholdNotMet))
+    );
+    assert_eq!(
+        check_auth(
+            sign(&[&phone_a, &phone_a]),
+            &deployee_address,
+            symbol_short!("update")
+        ),
+        Err(Ok(Error::SignerDuplicated))
+    );
+    assert_eq!(
+        check_auth(
+            sign(&[&phone_a, &phone_b]),
+            &deployee_address,
+            symbol_short!("update")
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        check_auth(
+            sign(&[&hardware]),
+            &deployee_address,
+            symbol_short!("update")
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        check_auth(sign(&[&phone_b]), &token, symbol_short!("transfer")),
+        Err(Ok(Error::ThresholdNotMet))
+    );
+    assert_eq!(
+        check_auth(sign(&[&hardware]), &token, symbol_short!("transfer")),
+        Ok(())
+    );
+    assert_eq!(
+        check_auth(sign(&[&phone_b]), &token, symbol_short!("balance")),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_policy() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+    let policy_address = env.register_contract(None, policy::Policy);
+
+    let signing_key = SigningKey::from_bytes(&[7; 32]);
+    let pk = BytesN::from_array(&env, &signing_key.verifying_key().to_bytes());
+    let id = Bytes::from_array(&env, &pk.to_array());
+
+    env.mock_all_auths();
+
+    deployee_client.add(&id, &SignerKey::Ed25519(pk), &true, &1, &None, &None, &None);
+    deployee_client.set_policies(&id, &vec![&env, policy_address]);
+
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+    let signature = Signature::Ed25519(Ed25519Signature {
+        id,
+        signature: BytesN::from_array(
+            &env,
+            &signing_key.sign(&signature_payload.to_array()).to_bytes(),
+        ),
+    });
+    let token = Address::generate(&env);
+    let check_auth = |fn_name| {
+        let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+            &deployee_address,
+            &signature_payload,
+            vec![&env, signature.clone()].into_val(&env),
+            &vec![
+                &env,
+                Context::Contract(ContractContext {
+                    contract: token.clone(),
+                    fn_name,
+                    args: vec![&env],
+                }),
+            ],
+        );
+
+        result
+    };
+
+    assert_eq!(check_auth(symbol_short!("approve")), Ok(()));
+    assert_eq!(
+        check_auth(symbol_short!("transfer")),
+        Err(Ok(Error::PolicyRejected))
+    );
+}
+
+#[test]
+fn test_limits() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let admin_key = SigningKey::from_bytes(&[1; 32]);
+    let session_key = SigningKey::from_bytes(&[2; 32]);
+    let session_id = Bytes::from_array(&env, &[2; 32]);
+    let token = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    deployee_client.add(
+        &Bytes::from_array(&env, &[1; 32]),
+        &SignerKey::Ed25519(BytesN::from_array(
+            &env,
+            &admin_key.verifying_key().to_bytes(),
+        )),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+    deployee_client.add(
+        &session_id,
+        &SignerKey::Ed25519(BytesN::from_array(
+            &env,
+            &session_key.verifying_key().to_bytes(),
+        )),
+        &false,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+    deployee_client.set_limits(
+        &session_id,
+        &map![
+            &env,
+            (
+                token.clone(),
+                SpendLimit {
+                    limit: 100,
+                    window: 10,
+                }
+            )
+        ],
+    );
+
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+    let signature = Signature::Ed25519(Ed25519Signature {
+        id: session_id.clone(),
+        signature: BytesN::from_array(
+            &env,
+            &session_key.sign(&signature_payload.to_array()).to_bytes(),
+        ),
+    });
+    let transfer = |amount: i128| {
+        let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+            &deployee_address,
+            &signature_payload,
+            vec![&env, signature.clone()].into_val(&env),
+            &vec![
+                &env,
+                Context::Contract(ContractContext {
+                    contract: token.clone(),
+                    fn_name: symbol_short!("transfer"),
+                    args: vec![
+                        &env,
+                        deployee_address.into_val(&env),
+                        Address::generate(&env).into_val(&env),
+                        amount.into_val(&env),
+                    ],
+                }),
+            ],
+        );
+
+        result
+    };
+
+    assert_eq!(transfer(60), Ok(()));
+    assert_eq!(transfer(60), Err(Ok(Error::SpendLimitExceeded)));
+    assert_eq!(transfer(40), Ok(()));
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 10);
+
+    assert_eq!(transfer(100), Ok(()));
+
+    // A window running past the last ledger never lets a spend drop out
+    deployee_client.set_limits(
+        &session_id,
+        &map![
+            &env,
+            (
+                token.clone(),
+                SpendLimit {
+                    limit: 100,
+                    window: u32::MAX,
+                }
+            )
+        ],
+    );
+
+    assert_eq!(transfer(1), Err(Ok(Error::SpendLimitExceeded)));
+}
+
+#[test]
+fn test_validity() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let admin_key = SigningKey::from_bytes(&[1; 32]);
+    let admin_id = Bytes::from_array(&env, &[1; 32]);
+    let session_key = SigningKey::from_bytes(&[2; 32]);
+    let session_id = Bytes::from_array(&env, &[2; 32]);
+    let sequence = env.ledger().sequence();
+
+    env.mock_all_auths();
+
+    deployee_client.add(
+        &admin_id,
+        &SignerKey::Ed25519(BytesN::from_array(
+            &env,
+            &admin_key.verifying_key().to_bytes(),
+        )),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+    deployee_client.add(
+        &session_id,
+        &SignerKey::Ed25519(BytesN::from_array(
+            &env,
+            &session_key.verifying_key().to_bytes(),
+        )),
+        &false,
+        &1,
+        &Some(sequence + 5),
+        &Some(sequence + 20),
+        &None,
+    );
+
+    assert_eq!(
+        deployee_client.try_add(
+            &Bytes::from_array(&env, &[3; 32]),
+            &SignerKey::Ed25519(BytesN::from_array(&env, &[3; 32])),
+            &false,
+            &1,
+            &Some(sequence + 5),
+            &Some(sequence + 4),
+            &None,
+        ),
+        Err(Ok(Error::SignerExpired))
+    );
+
+    // Admins count towards every threshold for as long as they exist
+    assert_eq!(
+        deployee_client.try_add(
+            &Bytes::from_array(&env, &[3; 32]),
+            &SignerKey::Ed25519(BytesN::from_array(&env, &[3; 32])),
+            &true,
+            &1,
+            &None,
+            &Some(sequence + 30),
+            &None,
+        ),
+        Err(Ok(Error::NotPermitted))
+    );
+
+    // Session signers don't live in storage past their expiry
+    env.as_contract(&deployee_address, || {
+        assert_eq!(
+            env.storage()
+                .temporary()
+                .get_ttl(&DataKey::Signer(session_id.clone())),
+            20
+        );
+    });
+
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+    let check_auth = |id: &Bytes, signing_key: &SigningKey| {
+        let signature = Signature::Ed25519(Ed25519Signature {
+            id: id.clone(),
+            signature: BytesN::from_array(
+                &env,
+                &signing_key.sign(&signature_payload.to_array()).to_bytes(),
+            ),
+        });
+        let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+            &deployee_address,
+            &signature_payload,
+            vec![&env, signature].into_val(&env),
+            &vec![&env],
+        );
+
+        result
+    };
+
+    assert_eq!(
+        check_auth(&session_id, &session_key),
+        Err(Ok(Error::SignerNotYetValid))
+    );
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 5);
+
+    assert_eq!(check_auth(&session_id, &session_key), Ok(()));
+
+    // Using a session signer doesn't extend it past its expiry
+    env.as_contract(&deployee_address, || {
+        assert_eq!(
+            env.storage()
+                .temporary()
+                .get_ttl(&DataKey::Signer(session_id.clone())),
+            15
+        );
+    });
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 16);
+
+    // Expired session signers are gone altogether, while admins never lapse
+    assert_eq!(
+        check_auth(&session_id, &session_key),
+        Err(Ok(Error::NotFound))
+    );
+    assert_eq!(check_auth(&admin_id, &admin_key), Ok(()));
+}
+
+#[test]
+fn test_scope() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let session_key = SigningKey::from_bytes(&[2; 32]);
+    let session_id = Bytes::from_array(&env, &[2; 32]);
+    let game = Address::generate(&env);
+    let token = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    deployee_client.add(
+        &Bytes::from_array(&env, &[1; 32]),
+        &SignerKey::Ed25519(BytesN::from_array(&env, &[1; 32])),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+    deployee_client.add(
+        &session_id,
+        &SignerKey::Ed25519(BytesN::from_array(
+            &env,
+            &session_key.verifying_key().to_bytes(),
+        )),
+        &false,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+    deployee_client.set_scope(
+        &session_id,
+        &map![&env, (game.clone(), vec![&env, symbol_short!("play")])],
+    );
+
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+    let signature = Signature::Ed25519(Ed25519Signature {
+        id: session_id.clone(),
+        signature: BytesN::from_array(
+            &env,
+            &session_key.sign(&signature_payload.to_array()).to_bytes(),
+        ),
+    });
+    let check_auth = |contract: &Address, fn_name, args| {
+        let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+            &deployee_address,
+            &signature_payload,
+            vec![&env, signature.clone()].into_val(&env),
+            &vec![
+                &env,
+                Context::Contract(ContractContext {
+                    contract: contract.clone(),
+                    fn_name,
+                    args,
+                }),
+            ],
+        );
+
+        result
+    };
+
+    assert_eq!(check_auth(&game, symbol_short!("play"), vec![&env]), Ok(()));
+    assert_eq!(
+        check_auth(&game, symbol_short!("withdraw"), vec![&env]),
+        Err(Ok(Error::NotPermitted))
+    );
+    assert_eq!(
+        check_auth(&token, symbol_short!("transfer"), vec![&env]),
+        Err(Ok(Error::NotPermitted))
+    );
+    assert_eq!(
+        check_auth(
+            &deployee_address,
+            symbol_short!("remove"),
+            vec![&env, session_id.into_val(&env)]
+        ),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_recovery() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let admin_key = SigningKey::from_bytes(&[1; 32]);
+    let admin_id = Bytes::from_array(&env, &[1; 32]);
+    let guardian_keys = [
+        SigningKey::from_bytes(&[3; 32]),
+        SigningKey::from_bytes(&[4; 32]),
+    ];
+    let guardian_ids = [
+        Bytes::from_array(&env, &[3; 32]),
+        Bytes::from_array(&env, &[4; 32]),
+    ];
+    let new_id = Bytes::from_array(&env, &[5; 32]);
+    let new_pk = secp256r1_pk(
+        &env,
+        &p256::ecdsa::SigningKey::from_slice(&[5; 32]).unwrap(),
+    );
+
+    env.mock_all_auths();
+
+    deployee_client.add(
+        &admin_id,
+        &SignerKey::Ed25519(BytesN::from_array(
+            &env,
+            &admin_key.verifying_key().to_bytes(),
+        )),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+
+    assert_eq!(
+        deployee_client.try_set_recovery(&Recovery {
+            threshold: 1,
+            delay: 10
+        }),
+        Err(Ok(Error::InvalidThreshold))
+    );
+
+    for (id, key) in guardian_ids.iter().zip(guardian_keys.iter()) {
+        deployee_client.add_guardian(
+            id,
+            &SignerKey::Ed25519(BytesN::from_array(&env, &key.verifying_key().to_bytes())),
+        );
+    }
+
+    // Signers and guardians can't share an id, or each would reset the other's sign count
+    assert_eq!(
+        deployee_client.try_add_guardian(
+            &admin_id,
+            &SignerKey::Ed25519(BytesN::from_array(
+                &env,
+                &admin_key.verifying_key().to_bytes(),
+            )),
+        ),
+        Err(Ok(Error::SignerDuplicated))
+    );
+    assert_eq!(
+        deployee_client.try_add(
+            &guardian_ids[0],
+            &SignerKey::Ed25519(BytesN::from_array(
+                &env,
+                &guardian_keys[0].verifying_key().to_bytes(),
+            )),
+            &false,
+            &1,
+            &None,
+            &None,
+            &None,
+        ),
+        Err(Ok(Error::SignerDuplicated))
+    );
+
+    deployee_client.set_recovery(&Recovery {
+        threshold: 2,
+        delay: 10,
+    });
+
+    assert_eq!(
+        deployee_client.try_remove_guardian(&guardian_ids[0]),
+        Err(Ok(Error::InvalidThreshold))
+    );
+
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+    let sign = |id: &Bytes, signing_key: &SigningKey| {
+        Signature::Ed25519(Ed25519Signature {
+            id: id.clone(),
+            signature: BytesN::from_array(
+                &env,
+                &signing_key.sign(&signature_payload.to_array()).to_bytes(),
+            ),
+        })
+    };
+    let check_auth = |signatures: soroban_sdk::Vec<Signature>, fn_name: &str, args| {
+        let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+            &deployee_address,
+            &signature_payload,
+            signatures.into_val(&env),
+            &vec![
+                &env,
+                Context::Contract(ContractContext {
+                    contract: deployee_address.clone(),
+                    fn_name: Symbol::new(&env, fn_name),
+                    args,
+                }),
+            ],
+        );
+
+        result
+    };
+    let recovery_args = vec![&env, new_id.into_val(&env), new_pk.into_val(&env)];
+
+    assert_eq!(
+        check_auth(
+            vec![&env, sign(&guardian_ids[0], &guardian_keys[0])],
+            "start_recovery",
+            recovery_args.clone()
+        ),
+        Err(Ok(Error::ThresholdNotMet))
+    );
+    assert_eq!(
+        check_auth(
+            vec![&env, sign(&admin_id, &admin_key)],
+            "start_recovery",
+            recovery_args.clone()
+        ),
+        Err(Ok(Error::ThresholdNotMet))
+    );
+    assert_eq!(
+        check_auth(
+            vec![
+                &env,
+                sign(&guardian_ids[0], &guardian_keys[0]),
+                sign(&guardian_ids[1], &guardian_keys[1])
+            ],
+            "start_recovery",
+            recovery_args.clone()
+        ),
+        Ok(())
+    );
+
+    // Guardians can't do anything else
+    assert_eq!(
+        check_auth(
+            vec![
+                &env,
+                sign(&guardian_ids[0], &guardian_keys[0]),
+                sign(&guardian_ids[1], &guardian_keys[1])
+            ],
+            "remove",
+            vec![&env, admin_id.into_val(&env)]
+        ),
+        Err(Ok(Error::NotPermitted))
+    );
+
+    // Any admin can veto a recovery
+    assert_eq!(
+        check_auth(
+            vec![&env, sign(&admin_id, &admin_key)],
+            "cancel_recovery",
+            vec![&env]
+        ),
+        Ok(())
+    );
+
+    deployee_client.start_recovery(&new_id, &new_pk);
+
+    assert_eq!(
+        deployee_client.try_start_recovery(&new_id, &new_pk),
+        Err(Ok(Error::RecoveryPending))
+    );
+    assert_eq!(
+        deployee_client.try_finish_recovery(),
+        Err(Ok(Error::RecoveryNotReady))
+    );
+
+    deployee_client.cancel_recovery();
+
+    assert_eq!(
+        deployee_client.try_finish_recovery(),
+        Err(Ok(Error::NotFound))
+    );
+
+    deployee_client.start_recovery(&new_id, &new_pk);
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 10);
+
+    deployee_client.finish_recovery();
+
+    env.as_contract(&deployee_address, || {
+        let signer: Signer = env
+            .storage()
+            .persistent()
+            .get(&DataKey::Signer(new_id.clone()))
+            .unwrap();
+
+        assert_eq!(signer.key, SignerKey::Secp256r1(new_pk.clone()));
+        assert_eq!(signer.weight, 1);
+    });
+}
+
+#[test]
+fn test_timelock() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let admin_id = Bytes::from_array(&env, &[1; 32]);
+    let new_admin_id = Bytes::from_array(&env, &[2; 32]);
+    let session_id = Bytes::from_array(&env, &[3; 32]);
+
+    env.mock_all_auths();
+
+    deployee_client.add(
+        &admin_id,
+        &SignerKey::Ed25519(BytesN::from_array(&env, &[1; 32])),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+    deployee_client.set_timelock(&10);
+
+    let has_signer = |id: &Bytes| {
+        env.as_contract(&deployee_address, || {
+            env.storage().persistent().has(&DataKey::Signer(id.clone()))
+        })
+    };
+
+    deployee_client.add(
+        &new_admin_id,
+        &SignerKey::Ed25519(BytesN::from_array(&env, &[2; 32])),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+
+    assert!(!has_signer(&new_admin_id));
+    assert_eq!(
+        deployee_client.try_execute_pending(&0),
+        Err(Ok(Error::TimelockNotReady))
+    );
+
+    // Session signers aren't held back
+    deployee_client.add(
+        &session_id,
+        &SignerKey::Ed25519(BytesN::from_array(&env, &[3; 32])),
+        &false,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+
+    env.as_contract(&deployee_address, || {
+        assert!(env
+            .storage()
+            .temporary()
+            .has(&DataKey::Signer(session_id.clone())));
+    });
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 10);
+
+    deployee_client.execute_pending(&0);
+
+    assert!(has_signer(&new_admin_id));
+    assert_eq!(
+        deployee_client.try_execute_pending(&0),
+        Err(Ok(Error::NotFound))
+    );
+
+    // Neither removing an admin nor turning off the timelock happen right away, and both can be cancelled
+    deployee_client.remove(&admin_id);
+    deployee_client.set_timelock(&0);
+
+    assert!(has_signer(&admin_id));
+
+    deployee_client.cancel_pending(&1);
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 10);
+
+    assert_eq!(
+        deployee_client.try_execute_pending(&1),
+        Err(Ok(Error::NotFound))
+    );
+
+    deployee_client.execute_pending(&2);
+    deployee_client.remove(&admin_id);
+
+    assert!(!has_signer(&admin_id));
+}
+
+#[test]
+fn test_timelock_recovery() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let guardian_id = Bytes::from_array(&env, &[3; 32]);
+
+    env.mock_all_auths();
+
+    deployee_client.add(
+        &Bytes::from_array(&env, &[1; 32]),
+        &SignerKey::Ed25519(BytesN::from_array(&env, &[1; 32])),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+    deployee_client.set_timelock(&1000);
+
+    // Guardians and recovery settings are held back like admins, or a single admin could recover the wallet to themselves right away
+    deployee_client.add_guardian(
+        &guardian_id,
+        &SignerKey::Ed25519(BytesN::from_array(&env, &[3; 32])),
+    );
+    deployee_client.set_recovery(&Recovery {
+        threshold: 1,
+        delay: 0,
+    });
+
+    env.as_contract(&deployee_address, || {
+        assert!(!env
+            .storage()
+            .persistent()
+            .has(&DataKey::Guardian(guardian_id.clone())));
+    });
+    assert_eq!(
+        deployee_client.try_start_recovery(
+            &Bytes::from_array(&env, &[5; 32]),
+            &secp256r1_pk(
+                &env,
+                &p256::ecdsa::SigningKey::from_slice(&[5; 32]).unwrap()
+            )
+        ),
+        Err(Ok(Error::NotFound))
+    );
+
+    env.ledger()
+        .with_mut(|ledger| ledger.sequence_number += 1000);
+
+    deployee_client.execute_pending(&0);
+
+    // Recoveries can't be quicker than the timelock
+    assert_eq!(
+        deployee_client.try_execute_pending(&1),
+        Err(Ok(Error::NotPermitted))
+    );
+
+    deployee_client.set_recovery(&Recovery {
+        threshold: 1,
+        delay: 1000,
+    });
+
+    env.ledger()
+        .with_mut(|ledger| ledger.sequence_number += 1000);
+
+    deployee_client.execute_pending(&2);
+
+    // Nor can the timelock outgrow them
+    deployee_client.set_timelock(&2000);
+
+    env.ledger()
+        .with_mut(|ledger| ledger.sequence_number += 1000);
+
+    assert_eq!(
+        deployee_client.try_execute_pending(&3),
+        Err(Ok(Error::NotPermitted))
+    );
+
+    deployee_client.remove_guardian(&guardian_id);
+
+    env.ledger()
+        .with_mut(|ledger| ledger.sequence_number += 1000);
+
+    // Still the one guardian the recovery threshold needs
+    assert_eq!(
+        deployee_client.try_execute_pending(&4),
+        Err(Ok(Error::InvalidThreshold))
+    );
+}
+
+#[test]
+fn test_timelock_veto() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+    let deny_policy_address = env.register_contract(None, deny_policy::Policy);
+
+    let rogue_id = Bytes::from_array(&env, &[1; 32]);
+    let admin_key = p256::ecdsa::SigningKey::from_slice(&[2; 32]).unwrap();
+    let admin_id = Bytes::from_array(&env, &[2; 32]);
+
+    env.mock_all_auths();
+
+    deployee_client.add(
+        &rogue_id,
+        &SignerKey::Ed25519(BytesN::from_array(&env, &[1; 32])),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+    deployee_client.add(
+        &admin_id,
+        &SignerKey::Secp256r1(secp256r1_pk(&env, &admin_key)),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+    deployee_client.set_webauthn(&Some(rp_id_hash(&env, RP_ID)), &false);
+    deployee_client.set_timelock(&10);
+
+    // A rogue admin queues an update, then tries to lock the other admin out of cancelling it
+    deployee_client.update(&BytesN::from_array(&env, &[0; 32]));
+    deployee_client.set_policies(&admin_id, &vec![&env, deny_policy_address]);
+    deployee_client.set_webauthn(&Some(rp_id_hash(&env, "rogue.example")), &true);
+    deployee_client.set_origins(
+        &vec![&env, Bytes::from_slice(&env, b"https://rogue.example")],
+        &false,
+    );
+
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+    let check_auth = |sign_count: u32, fn_name: &str, args: soroban_sdk::Vec<Val>| {
+        let signature = sign_secp256r1(
+            &env,
+            &admin_id,
+            &admin_key,
+            &signature_payload,
+            authenticator_data(&env, RP_ID, 0x01, sign_count),
+        );
+        let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+            &deployee_address,
+            &signature_payload,
+            vec![&env, signature].into_val(&env),
+            &vec![
+                &env,
+                Context::Contract(ContractContext {
+                    contract: deployee_address.clone(),
+                    fn_name: Symbol::new(&env, fn_name),
+                    args,
+                }),
+            ],
+        );
+
+        result
+    };
+
+    assert_eq!(
+        check_auth(1, "set_policies", vec![&env]),
+        Err(Ok(Error::PolicyRejected))
+    );
+    assert_eq!(
+        check_auth(2, "cancel_pending", vec![&env, 0u32.into_val(&env)]),
+        Ok(())
+    );
+
+    deployee_client.cancel_pending(&0);
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 10);
+
+    assert_eq!(
+        deployee_client.try_execute_pending(&0),
+        Err(Ok(Error::NotFound))
+    );
+}
+
+#[test]
+fn test_rotate() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let admin_id = Bytes::from_array(&env, &[1; 32]);
+    let new_admin_key = SigningKey::from_bytes(&[2; 32]);
+    let new_admin_id = Bytes::from_array(&env, &[2; 32]);
+    let session_id = Bytes::from_array(&env, &[3; 32]);
+    let new_session_id = Bytes::from_array(&env, &[4; 32]);
+    let game = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    deployee_client.add(
+        &admin_id,
+        &SignerKey::Ed25519(BytesN::from_array(&env, &[1; 32])),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+    deployee_client.add(
+        &session_id,
+        &SignerKey::Ed25519(BytesN::from_array(&env, &[3; 32])),
+        &false,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+    deployee_client.set_scope(&session_id, &map![&env, (game.clone(), vec![&env])]);
+
+    assert_eq!(
+        deployee_client.try_rotate(
+            &admin_id,
+            &session_id,
+            &SignerKey::Ed25519(BytesN::from_array(&env, &[5; 32]))
+        ),
+        Err(Ok(Error::SignerDuplicated))
+    );
+
+    // The last admin can be rotated
+    deployee_client.rotate(
+        &admin_id,
+        &new_admin_id,
+        &SignerKey::Ed25519(BytesN::from_array(
+            &env,
+            &new_admin_key.verifying_key().to_bytes(),
+        )),
+    );
+    deployee_client.rotate(
+        &session_id,
+        &new_session_id,
+        &SignerKey::Ed25519(BytesN::from_array(&env, &[4; 32])),
+    );
+
+    env.as_contract(&deployee_address, || {
+        assert!(!env
+            .storage()
+            .persistent()
+            .has(&DataKey::Signer(admin_id.clone())));
+        assert!(!env
+            .storage()
+            .temporary()
+            .has(&DataKey::Signer(session_id.clone())));
+
+        let signer: Signer = env
+            .storage()
+            .temporary()
+            .get(&DataKey::Signer(new_session_id.clone()))
+            .unwrap();
+
+        assert_eq!(signer.scope, map![&env, (game.clone(), vec![&env])]);
+    });
+
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+    let signature = Signature::Ed25519(Ed25519Signature {
+        id: new_admin_id.clone(),
+        signature: BytesN::from_array(
+            &env,
+            &new_admin_key.sign(&signature_payload.to_array()).to_bytes(),
+        ),
+    });
+    let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+        &deployee_address,
+        &signature_payload,
+        vec![&env, signature].into_val(&env),
+        &vec![
+            &env,
+            Context::Contract(ContractContext {
+                contract: deployee_address.clone(),
+                fn_name: symbol_short!("remove"),
+                args: vec![&env, new_session_id.into_val(&env)],
+            }),
+        ],
+    );
+
+    assert_eq!(result, Ok(()));
+}
+
+#[test]
+fn test_rotate_queued() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let a_id = Bytes::from_array(&env, &[1; 32]);
+    let b_id = Bytes::from_array(&env, &[2; 32]);
+    let c_id = Bytes::from_array(&env, &[3; 32]);
+    let c_key = SignerKey::Ed25519(BytesN::from_array(&env, &[3; 32]));
+
+    env.mock_all_auths();
+
+    deployee_client.add(
+        &a_id,
+        &SignerKey::Ed25519(BytesN::from_array(&env, &[1; 32])),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+    deployee_client.add(
+        &b_id,
+        &SignerKey::Ed25519(BytesN::from_array(&env, &[2; 32])),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+    deployee_client.set_timelock(&10);
+
+    deployee_client.rotate(&a_id, &c_id, &c_key);
+    deployee_client.add(&c_id, &c_key, &true, &1, &None, &None, &None);
+    deployee_client.remove(&b_id);
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 10);
+
+    // The rotation was queued first but can't overwrite the admin added since
+    deployee_client.execute_pending(&1);
+
+    assert_eq!(
+        deployee_client.try_execute_pending(&0),
+        Err(Ok(Error::SignerDuplicated))
+    );
+    assert_eq!(deployee_client.admin_count(), 3);
+    assert_eq!(deployee_client.list_signers().len(), 3);
+
+    // Nor rotate an admin removed since
+    deployee_client.rotate(&b_id, &Bytes::from_array(&env, &[4; 32]), &c_key);
+    deployee_client.execute_pending(&2);
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 10);
+
+    assert_eq!(
+        deployee_client.try_execute_pending(&3),
+        Err(Ok(Error::NotFound))
+    );
+    assert_eq!(deployee_client.admin_count(), 2);
+}
+
+#[test]
+fn test_sign_count() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let counting_key = p256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+    let counting_id = Bytes::from_array(&env, &[1; 20]);
+    let zero_key = p256::ecdsa::SigningKey::from_slice(&[2; 32]).unwrap();
+    let zero_id = Bytes::from_array(&env, &[2; 20]);
+
+    env.mock_all_auths();
+
+    deployee_client.add(
+        &counting_id,
+        &SignerKey::Secp256r1(secp256r1_pk(&env, &counting_key)),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+    deployee_client.add(
+        &zero_id,
+        &SignerKey::Secp256r1(secp256r1_pk(&env, &zero_key)),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+    let check_auth = |id: &Bytes, signing_key, sign_count| {
+        let signature = sign_secp256r1(
+            &env,
+            id,
+            signing_key,
+            &signature_payload,
+            authenticator_data(&env, RP_ID, 0x05, sign_count),
+        );
+        let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+            &deployee_address,
+            &signature_payload,
+            vec![&env, signature].into_val(&env),
+            &vec![&env],
+        );
+
+        result
+    };
+
+    assert_eq!(check_auth(&counting_id, &counting_key, 5), Ok(()));
+    assert_eq!(
+        check_auth(&counting_id, &counting_key, 5),
+        Err(Ok(Error::SignCountInvalid))
+    );
+    assert_eq!(
+        check_auth(&counting_id, &counting_key, 0),
+        Err(Ok(Error::SignCountInvalid))
+    );
+    assert_eq!(check_auth(&counting_id, &counting_key, 6), Ok(()));
+
+    // Authenticators which don't count keep working
+    assert_eq!(check_auth(&zero_id, &zero_key, 0), Ok(()));
+    assert_eq!(check_auth(&zero_id, &zero_key, 0), Ok(()));
+}
+
+#[test]
+fn test_webauthn() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let admin_key = p256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+    let admin_id = Bytes::from_array(&env, &[1; 20]);
+    let session_key = p256::ecdsa::SigningKey::from_slice(&[2; 32]).unwrap();
+    let session_id = Bytes::from_array(&env, &[2; 20]);
+
+    deployee_client.init(
+        &admin_id,
+        &SignerKey::Secp256r1(secp256r1_pk(&env, &admin_key)),
+        &Some(rp_id_hash(&env, RP_ID)),
+    );
+
+    env.mock_all_auths();
+
+    deployee_client.add(
+        &session_id,
+        &SignerKey::Secp256r1(secp256r1_pk(&env, &session_key)),
+        &false,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+    let check_auth = |id: &Bytes, signing_key, authenticator_data| {
+        let signature = sign_secp256r1(
+            &env,
+            id,
+            signing_key,
+            &signature_payload,
+            authenticator_data,
+        );
+        let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+            &deployee_address,
+            &signature_payload,
+            vec![&env, signature].into_val(&env),
+            &vec![&env],
+        );
+
+        result
+    };
+
+    assert_eq!(
+        check_auth(
+            &admin_id,
+            &admin_key,
+            authenticator_data(&env, "evil.example", 0x05, 0)
+        ),
+        Err(Ok(Error::RpIdHashMismatch))
+    );
+    assert_eq!(
+        check_auth(
+            &admin_id,
+            &admin_key,
+            authenticator_data(&env, RP_ID, 0x00, 0)
+        ),
+        Err(Ok(Error::UserPresenceRequired))
+    );
+    assert_eq!(
+        check_auth(
+            &admin_id,
+            &admin_key,
+            authenticator_data(&env, RP_ID, 0x01, 0)
+        ),
+        Ok(())
+    );
+
+    // Flags are only enforced for admins
+    assert_eq!(
+        check_auth(
+            &session_id,
+            &session_key,
+            authenticator_data(&env, RP_ID, 0x00, 0)
+        ),
+        Ok(())
+    );
+
+    deployee_client.set_webauthn(&Some(rp_id_hash(&env, RP_ID)), &true);
+
+    assert_eq!(
+        check_auth(
+            &admin_id,
+            &admin_key,
+            authenticator_data(&env, RP_ID, 0x01, 0)
+        ),
+        Err(Ok(Error::UserVerificationRequired))
+    );
+    assert_eq!(
+        check_auth(
+            &admin_id,
+            &admin_key,
+            authenticator_data(&env, RP_ID, 0x05, 0)
+        ),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_client_data_json() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let signing_key = p256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+    let id = Bytes::from_array(&env, &[1; 20]);
+
+    env.mock_all_auths();
+
+    deployee_client.add(
+        &id,
+        &SignerKey::Secp256r1(secp256r1_pk(&env, &signing_key)),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+    let check_auth = |type_, origin, cross_origin| {
+        let signature = sign_client_data_json(
+            &env,
+            &id,
+            &signing_key,
+            authenticator_data(&env, RP_ID, 0x05, 0),
+            client_data_json(&env, type_, &signature_payload, origin, cross_origin),
+        );
+        let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+            &deployee_address,
+            &signature_payload,
+            vec![&env, signature].into_val(&env),
+            &vec![&env],
+        );
+
+        result
+    };
+
+    assert_eq!(
+        check_auth("webauthn.create", ORIGIN, false),
+        Err(Ok(Error::ClientDataJsonTypeIncorrect))
+    );
+
+    // Any origin until there's an allowlist
+    assert_eq!(
+        check_auth("webauthn.get", "https://evil.example", false),
+        Ok(())
+    );
+
+    deployee_client.set_origins(
+        &vec![&env, Bytes::from_slice(&env, ORIGIN.as_bytes())],
+        &false,
+    );
+
+    assert_eq!(
+        check_auth("webauthn.get", "https://evil.example", false),
+        Err(Ok(Error::ClientDataJsonOriginNotAllowed))
+    );
+    assert_eq!(check_auth("webauthn.get", ORIGIN, false), Ok(()));
+    assert_eq!(
+        check_auth("webauthn.get", ORIGIN, true),
+        Err(Ok(Error::ClientDataJsonCrossOrigin))
+    );
+
+    deployee_client.set_origins(
+        &vec![&env, Bytes::from_slice(&env, ORIGIN.as_bytes())],
+        &true,
+    );
+
+    assert_eq!(check_auth("webauthn.get", ORIGIN, true), Ok(()));
+}
+
+#[test]
+fn test_client_data_json_len() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let signing_key = p256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+    let id = Bytes::from_array(&env, &[1; 20]);
+
+    env.mock_all_auths();
+
+    deployee_client.add(
+        &id,
+        &SignerKey::Secp256r1(secp256r1_pk(&env, &signing_key)),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+    let mut challenge = [0u8; 43];
+
+    crate::base64_url::encode(&mut challenge, &signature_payload.to_array());
+
+    let challenge = std::str::from_utf8(&challenge).unwrap();
+    let check_auth = |client_data_json: std::string::String| {
+        let signature = sign_client_data_json(
+            &env,
+            &id,
+            &signing_key,
+            authenticator_data(&env, RP_ID, 0x05, 0),
+            Bytes::from_slice(&env, client_data_json.as_bytes()),
+        );
+        let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+            &deployee_address,
+            &signature_payload,
+            vec![&env, signature].into_val(&env),
+            &vec![&env],
+        );
+
+        result
+    };
+    let padding = "x".repeat(2000);
+
+    // Extra members of any shape and well past the old 1024 bytes
+    assert_eq!(
+        check_auth(std::format!(
+            r#"{{ "type": "webauthn.get", "challenge": "{challenge}", "origin": "{ORIGIN}", "crossOrigin": false, "other_keys_can_be_added_here": "do not compare clientDataJSON against a template. See https:\/\/goo.gl\/yabPex", "androidPackageName": "{padding}", "nested": {{ "a": [1, -2.5e3, null, true, {{ "b": "\"}}" }}] }} }}"#
+        )),
+        Ok(())
+    );
+    assert_eq!(
+        check_auth(std::format!(
+            r#"{{"type":"webauthn.get","challenge":"{challenge}","origin":"{ORIGIN}","padding":"{padding}{padding}{padding}"}}"#
+        )),
+        Err(Ok(Error::ClientDataJsonTooLong))
+    );
+    assert_eq!(
+        check_auth(std::format!(
+            r#"{{"type":"webauthn.get","challenge":"{challenge}","origin":"{ORIGIN}""#
+        )),
+        Err(Ok(Error::JsonParseError))
+    );
+    assert_eq!(
+        check_auth(std::format!(
+            r#"{{"type":"webauthn.get","challenge":"{challenge}","challenge":"{challenge}","origin":"{ORIGIN}"}}"#
+        )),
+        Err(Ok(Error::JsonParseError))
+    );
+    assert_eq!(
+        check_auth(std::format!(
+            r#"{{"type":"webauthn.get","origin":"{ORIGIN}"}}"#
+        )),
+        Err(Ok(Error::JsonParseError))
+    );
+}
+
+#[test]
+fn test_signature_encoding() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let signing_key = p256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+    let id = Bytes::from_array(&env, &[1; 20]);
+
+    env.mock_all_auths();
+
+    deployee_client.add(
+        &id,
+        &SignerKey::Secp256r1(secp256r1_pk(&env, &signing_key)),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+    let Signature::Secp256r1(signature) = sign_secp256r1(
+        &env,
+        &id,
+        &signing_key,
+        &signature_payload,
+        authenticator_data(&env, RP_ID, 0x05, 0),
+    ) else {
+        unreachable!()
+    };
+    let compact = p256::ecdsa::Signature::from_slice(
+        &signature.signature.iter().collect::<std::vec::Vec<u8>>(),
+    )
+    .unwrap();
+    let (r, s) = compact.split_scalars();
+    let high_s = p256::ecdsa::Signature::from_scalars(r.to_bytes(), (-*s).to_bytes()).unwrap();
+    let check_auth = |encoded: &[u8]| {
+        let mut signature = signature.clone();
+
+        signature.signature = Bytes::from_slice(&env, encoded);
+
+        let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+            &deployee_address,
+            &signature_payload,
+            vec![&env, Signature::Secp256r1(signature)].into_val(&env),
+            &vec![&env],
+        );
+
+        result
+    };
+
+    assert_eq!(check_auth(&compact.to_bytes()), Ok(()));
+    assert_eq!(check_auth(compact.to_der().as_bytes()), Ok(()));
+    assert_eq!(check_auth(&high_s.to_bytes()), Ok(()));
+    assert_eq!(check_auth(high_s.to_der().as_bytes()), Ok(()));
+
+    let mut der = compact.to_der().as_bytes().to_vec();
+
+    der[0] = 0x31;
+
+    assert_eq!(check_auth(&der), Err(Ok(Error::Secp256r1SignatureParse)));
+    assert_eq!(
+        check_auth(&compact.to_der().as_bytes()[..20]),
+        Err(Ok(Error::Secp256r1SignatureParse))
+    );
+
+    // `0x30 0x3e` followed by two 29 byte integers is DER, not `r || s`
+    let mut der = [0u8; 64];
+
+    der[..4].copy_from_slice(&[0x30, 0x3e, 0x02, 0x1d]);
+    der[4..33].fill(0x11);
+    der[33..35].copy_from_slice(&[0x02, 0x1d]);
+    der[35..].fill(0x22);
+
+    let mut expected = [0u8; 64];
+
+    expected[3..32].fill(0x11);
+    expected[35..].fill(0x22);
+
+    assert_eq!(
+        crate::secp256r1::parse_signature(&env, &Bytes::from_array(&env, &der)),
+        Ok(BytesN::from_array(&env, &expected))
+    );
+
+    // Whereas 64 bytes that don't parse as DER still are
+    der[1] = 0x3f;
+
+    assert_eq!(
+        crate::secp256r1::parse_signature(&env, &Bytes::from_array(&env, &der)),
+        Ok(BytesN::from_array(&env, &der))
+    );
+}
+
+#[test]
+fn test_secp256r1_parse() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let signing_key = p256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+    let id = Bytes::from_array(&env, &[1; 20]);
+    let pk = secp256r1_pk(&env, &signing_key);
+
+    env.mock_all_auths();
+
+    let mut compressed = pk.to_array();
+
+    compressed[0] = 0x02;
+
+    let mut off_curve = pk.to_array();
+
+    off_curve[64] ^= 1;
+
+    for bad_pk in [compressed, off_curve, [0xff; 65]] {
+        assert_eq!(
+            deployee_client.try_add(
+                &id,
+                &SignerKey::Secp256r1(BytesN::from_array(&env, &bad_pk)),
+                &true,
+                &1,
+                &None,
+                &None,
+                &None,
+            ),
+            Err(Ok(Error::Secp256r1PublicKeyParse))
+        );
+    }
+
+    deployee_client.add(
+        &id,
+        &SignerKey::Secp256r1(pk),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+    let Signature::Secp256r1(signature) = sign_secp256r1(
+        &env,
+        &id,
+        &signing_key,
+        &signature_payload,
+        authenticator_data(&env, RP_ID, 0x05, 0),
+    ) else {
+        unreachable!()
+    };
+    let check_auth = |compact: [u8; 64]| {
+        let mut signature = signature.clone();
+
+        signature.signature = Bytes::from_array(&env, &compact);
+
+        let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+            &deployee_address,
+            &signature_payload,
+            vec![&env, Signature::Secp256r1(signature)].into_val(&env),
+            &vec![&env],
+        );
+
+        result
+    };
+
+    // r and s must both be in [1, n - 1]
+    let mut zero_r = [1; 64];
+
+    zero_r[..32].copy_from_slice(&[0; 32]);
+
+    assert_eq!(check_auth(zero_r), Err(Ok(Error::Secp256r1SignatureParse)));
+    assert_eq!(
+        check_auth([0xff; 64]),
+        Err(Ok(Error::Secp256r1SignatureParse))
+    );
+}
+
+#[test]
+fn test_list_signers() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let admin_id = Bytes::from_array(&env, &[1; 32]);
+    let session_id = Bytes::from_array(&env, &[2; 32]);
+    let rotated_id = Bytes::from_array(&env, &[3; 32]);
+    let admin_key = SignerKey::Ed25519(BytesN::from_array(&env, &[1; 32]));
+    let session_key = SignerKey::Ed25519(BytesN::from_array(&env, &[2; 32]));
+
+    env.mock_all_auths();
+
+    deployee_client.add(&admin_id, &admin_key, &true, &1, &None, &None, &None);
+    deployee_client.add(
+        &session_id,
+        &session_key,
+        &false,
+        &1,
+        &None,
+        &Some(100),
+        &None,
+    );
+
+    let session = deployee_client.get_signer(&session_id);
+
+    assert!(!session.admin);
+    assert_eq!(session.signer.key, session_key);
+    assert_eq!(session.signer.valid_until, 100);
+    assert_eq!(
+        deployee_client.try_get_signer(&rotated_id),
+        Err(Ok(Error::NotFound))
+    );
+    assert_eq!(deployee_client.admin_count(), 1);
+
+    // Promoting doesn't list a signer twice
+    deployee_client.add(&session_id, &session_key, &true, &1, &None, &None, &None);
+
+    assert_eq!(deployee_client.admin_count(), 2);
+    assert_eq!(deployee_client.list_signers().len(), 2);
+
+    deployee_client.rotate(&session_id, &rotated_id, &session_key);
+    deployee_client.remove(&admin_id);
+
+    let signers = deployee_client.list_signers();
+
+    assert_eq!(signers.len(), 1);
+    assert_eq!(signers.get(0).unwrap().id, rotated_id);
+    assert!(signers.get(0).unwrap().admin);
+    assert_eq!(deployee_client.admin_count(), 1);
+}
+
+#[test]
+fn test_metadata() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let signing_key = SigningKey::from_bytes(&[1; 32]);
+    let id = Bytes::from_array(&env, &[1; 32]);
+    let key = SignerKey::Ed25519(BytesN::from_array(
+        &env,
+        &signing_key.verifying_key().to_bytes(),
+    ));
+    let sequence = env.ledger().sequence();
+
+    env.mock_all_auths();
+
+    assert_eq!(
+        deployee_client.try_add(
+            &id,
+            &key,
+            &true,
+            &1,
+            &None,
+            &None,
+            &Some(String::from_str(&env, &"x".repeat(33)))
+        ),
+        Err(Ok(Error::LabelTooLong))
+    );
+
+    deployee_client.add(
+        &id,
+        &key,
+        &true,
+        &1,
+        &None,
+        &None,
+        &Some(String::from_str(&env, "iPhone")),
+    );
+
+    env.ledger().with_mut(|ledger| ledger.sequence_number += 10);
+
+    // Relabelling keeps the original creation ledger
+    deployee_client.add(
+        &id,
+        &key,
+        &true,
+        &1,
+        &None,
+        &None,
+        &Some(String::from_str(&env, "Work iPhone")),
+    );
+
+    let signer = deployee_client.get_signer(&id);
+
+    assert_eq!(signer.signer.label, String::from_str(&env, "Work iPhone"));
+    assert_eq!(signer.signer.created, sequence);
+    assert_eq!(signer.last_used, 0);
+
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+    let signature = Signature::Ed25519(Ed25519Signature {
+        id: id.clone(),
+        signature: BytesN::from_array(
+            &env,
+            &signing_key.sign(&signature_payload.to_array()).to_bytes(),
+        ),
+    });
+
+    env.try_invoke_contract_check_auth::<Error>(
+        &deployee_address,
+        &signature_payload,
+        vec![&env, signature].into_val(&env),
+        &vec![&env],
+    )
+    .unwrap();
+
+    assert_eq!(deployee_client.get_signer(&id).last_used, sequence + 10);
+}
+
+#[test]
+fn test_readd() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+    let policy_address = env.register_contract(None, policy::Policy);
+
+    let session_id = Bytes::from_array(&env, &[2; 32]);
+    let session_key = SignerKey::Ed25519(BytesN::from_array(&env, &[2; 32]));
+    let token = Address::generate(&env);
+    let limits = map![
+        &env,
+        (
+            token.clone(),
+            SpendLimit {
+                limit: 100,
+                window: 10,
+            }
+        )
+    ];
+    let scope = map![&env, (token, vec![&env, symbol_short!("transfer")])];
+    let policies = vec![&env, policy_address];
+
+    env.mock_all_auths();
+
+    deployee_client.add(
+        &Bytes::from_array(&env, &[1; 32]),
+        &SignerKey::Ed25519(BytesN::from_array(&env, &[1; 32])),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+    deployee_client.add(&session_id, &session_key, &false, &1, &None, &None, &None);
+    deployee_client.set_limits(&session_id, &limits);
+    deployee_client.set_scope(&session_id, &scope);
+    deployee_client.set_policies(&session_id, &policies);
+
+    // Relabelling or moving the validity window mustn't lift any restrictions
+    deployee_client.add(
+        &session_id,
+        &session_key,
+        &false,
+        &1,
+        &None,
+        &Some(env.ledger().sequence() + 100),
+        &Some(String::from_str(&env, "Game")),
+    );
+
+    let signer = deployee_client.get_signer(&session_id).signer;
+
+    assert_eq!(signer.label, String::from_str(&env, "Game"));
+    assert_eq!(signer.limits, limits);
+    assert_eq!(signer.scope, scope);
+    assert_eq!(signer.policies, policies);
+
+    // Admins keep their policies but have no use for limits or scopes
+    deployee_client.add(&session_id, &session_key, &true, &1, &None, &None, &None);
+
+    let signer = deployee_client.get_signer(&session_id).signer;
+
+    assert!(signer.limits.is_empty());
+    assert!(signer.scope.is_empty());
+    assert_eq!(signer.policies, policies);
+}
+
+#[test]
+fn test_migrate() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let admin_key = p256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+    let admin_id = Bytes::from_array(&env, &[1; 20]);
+    let other_admin_key = p256::ecdsa::SigningKey::from_slice(&[2; 32]).unwrap();
+    let other_admin_id = Bytes::from_array(&env, &[2; 20]);
+    let session_key = p256::ecdsa::SigningKey::from_slice(&[3; 32]).unwrap();
+    let session_id = Bytes::from_array(&env, &[3; 20]);
+
+    // As left behind by a wallet deployed before `DataKey`
+    env.as_contract(&deployee_address, || {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("admins"), &2i32);
+        env.storage()
+            .persistent()
+            .set(&admin_id, &secp256r1_pk(&env, &admin_key));
+        env.storage()
+            .persistent()
+            .set(&other_admin_id, &secp256r1_pk(&env, &other_admin_key));
+        env.storage()
+            .temporary()
+            .set(&session_id, &secp256r1_pk(&env, &session_key));
+        env.storage().temporary().set(
+            &Bytes::from_array(&env, &[4; 20]),
+            &BytesN::from_array(&env, &[4; 65]),
+        );
+    });
+
+    // Legacy signers can still authorize, and are migrated as they do
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+    let signature = sign_secp256r1(
+        &env,
+        &admin_id,
+        &admin_key,
+        &signature_payload,
+        authenticator_data(&env, RP_ID, 0x05, 0),
+    );
+    let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+        &deployee_address,
+        &signature_payload,
+        vec![&env, signature].into_val(&env),
+        &vec![&env],
+    );
+
+    assert_eq!(result, Ok(()));
+
+    let admin = deployee_client.get_signer(&admin_id);
+
+    assert!(admin.admin);
+    assert_eq!(
+        admin.signer.key,
+        SignerKey::Secp256r1(secp256r1_pk(&env, &admin_key))
+    );
+    assert_eq!(admin.signer.weight, 1);
+    assert_eq!(deployee_client.admin_count(), 1);
+
+    env.as_contract(&deployee_address, || {
+        assert!(!env.storage().persistent().has(&admin_id));
+        assert!(!env.storage().instance().has(&symbol_short!("admins")));
+    });
+
+    env.mock_all_auths();
+
+    deployee_client.migrate(&vec![
+        &env,
+        session_id.clone(),
+        other_admin_id.clone(),
+        admin_id.clone(),
+        Bytes::from_array(&env, &[4; 20]),
+    ]);
+
+    assert!(!deployee_client.get_signer(&session_id).admin);
+    assert!(deployee_client.get_signer(&other_admin_id).admin);
+    assert_eq!(deployee_client.list_signers().len(), 3);
+    assert_eq!(deployee_client.admin_count(), 2);
+
+    // Raw ids are only looked up until `migrate` has run
+    env.as_contract(&deployee_address, || {
+        env.storage().persistent().set(
+            &Bytes::from_array(&env, &[5; 20]),
+            &secp256r1_pk(&env, &admin_key),
+        );
+    });
+
+    deployee_client.migrate(&vec![&env, Bytes::from_array(&env, &[5; 20])]);
+
+    assert_eq!(deployee_client.list_signers().len(), 3);
+
+    // Keys the host could never verify against are dropped rather than migrated
+    env.as_contract(&deployee_address, || {
+        assert!(!env.storage().temporary().has(&session_id));
+        assert!(!env.storage().persistent().has(&other_admin_id));
+        assert!(!env
+            .storage()
+            .temporary()
+            .has(&Bytes::from_array(&env, &[4; 20])));
+    });
+
+    // Migrated admins are already counted
+    deployee_client.add(
+        &other_admin_id,
+        &SignerKey::Secp256r1(secp256r1_pk(&env, &other_admin_key)),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+
+    assert_eq!(deployee_client.admin_count(), 2);
+
+    deployee_client.remove(&other_admin_id);
+
+    assert_eq!(deployee_client.admin_count(), 1);
+}
+
+#[test]
+fn test_migrate_drifted() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let admin_ids = [
+        Bytes::from_array(&env, &[1; 20]),
+        Bytes::from_array(&env, &[2; 20]),
+    ];
+
+    // Legacy wallets counted an admin again each time it was re-added
+    env.as_contract(&deployee_address, || {
+        env.storage()
+            .instance()
+            .set(&symbol_short!("admins"), &5i32);
+
+        for (i, id) in admin_ids.iter().enumerate() {
+            env.storage().persistent().set(
+                id,
+                &secp256r1_pk(
+                    &env,
+                    &p256::ecdsa::SigningKey::from_slice(&[i as u8 + 1; 32]).unwrap(),
+                ),
+            );
+        }
+    });
+
+    env.mock_all_auths();
+
+    // Still can't be claimed before every admin has been migrated
+    assert_eq!(
+        deployee_client.try_init(
+            &Bytes::from_array(&env, &[3; 20]),
+            &SignerKey::Ed25519(BytesN::from_array(&env, &[3; 32])),
+            &None,
+        ),
+        Err(Ok(Error::AlreadyInitialized))
+    );
+
+    deployee_client.migrate(&vec![&env, admin_ids[0].clone(), admin_ids[1].clone()]);
+
+    assert_eq!(deployee_client.admin_count(), 2);
+    assert_eq!(
+        deployee_client.try_set_thresholds(&Thresholds {
+            admin: 3,
+            transfer: 1,
+            default: 1,
+        }),
+        Err(Ok(Error::InvalidThreshold))
+    );
+
+    // As left by migrating before admins were counted one by one
+    env.as_contract(&deployee_address, || {
+        env.storage().instance().set(&DataKey::AdminCount, &5i32);
+        env.storage().instance().set(&DataKey::AdminWeight, &5u32);
+    });
+
+    deployee_client.set_thresholds(&Thresholds {
+        admin: 3,
+        transfer: 1,
+        default: 1,
+    });
+
+    // The recount can't leave a threshold the admins no longer meet
+    assert_eq!(
+        deployee_client.try_migrate(&vec![&env]),
+        Err(Ok(Error::NotPermitted))
+    );
+    assert_eq!(deployee_client.admin_count(), 5);
+
+    deployee_client.set_thresholds(&Thresholds {
+        admin: 1,
+        transfer: 1,
+        default: 1,
+    });
+    deployee_client.migrate(&vec![&env]);
+
+    assert_eq!(deployee_client.admin_count(), 2);
+    assert_eq!(
+        deployee_client.try_set_thresholds(&Thresholds {
+            admin: 3,
+            transfer: 1,
+            default: 1,
+        }),
+        Err(Ok(Error::InvalidThreshold))
+    );
+
+    deployee_client.set_thresholds(&Thresholds {
+        admin: 2,
+        transfer: 1,
+        default: 1,
+    });
+}
+
+#[test]
+fn test_init() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let id = Bytes::from_array(&env, &[1; 32]);
+    let key = SignerKey::Ed25519(BytesN::from_array(&env, &[1; 32]));
+    let other_id = Bytes::from_array(&env, &[2; 32]);
+    let other_key = SignerKey::Ed25519(BytesN::from_array(&env, &[2; 32]));
+
+    deployee_client.init(&id, &key, &Some(rp_id_hash(&env, RP_ID)));
+
+    assert_eq!(
+        deployee_client.try_init(&other_id, &other_key, &None),
+        Err(Ok(Error::AlreadyInitialized))
+    );
+
+    let admin = deployee_client.get_signer(&id);
+
+    assert!(admin.admin);
+    assert_eq!(admin.signer.weight, 1);
+    assert_eq!(deployee_client.admin_count(), 1);
+
+    env.mock_all_auths();
+
+    // Every signer after the first needs the wallet's approval
+    deployee_client.add(&other_id, &other_key, &true, &1, &None, &None, &None);
+
+    assert_eq!(env.auths()[0].0, deployee_address);
+}
+
+#[test]
+fn test_exec() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let session_key = SigningKey::from_bytes(&[2; 32]);
+    let session_id = Bytes::from_array(&env, &[2; 32]);
+    let token = env.register_stellar_asset_contract(Address::generate(&env));
+    let recipient = Address::generate(&env);
+
+    env.mock_all_auths();
+
+    deployee_client.init(
+        &Bytes::from_array(&env, &[1; 32]),
+        &SignerKey::Ed25519(BytesN::from_array(&env, &[1; 32])),
+        &None,
+    );
+    deployee_client.add(
+        &session_id,
+        &SignerKey::Ed25519(BytesN::from_array(
+            &env,
+            &session_key.verifying_key().to_bytes(),
+        )),
+        &false,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+    deployee_client.set_limits(
+        &session_id,
+        &map![
+            &env,
+            (
+                token.clone(),
+                SpendLimit {
+                    limit: 100,
+                    window: 10,
+                }
+            )
+        ],
+    );
+    deployee_client.set_scope(&session_id, &map![&env, (token.clone(), vec![&env])]);
+
+    token::StellarAssetClient::new(&env, &token).mint(&deployee_address, &100);
+
+    let transfer = |amount: i128| {
+        (
+            token.clone(),
+            symbol_short!("transfer"),
+            vec![
+                &env,
+                deployee_address.into_val(&env),
+                recipient.into_val(&env),
+                amount.into_val(&env),
+            ],
+        )
+    };
+
+    // Every call is made, in order, with its result returned
+    let results = deployee_client.exec(&vec![
+        &env,
+        transfer(30),
+        (
+            token.clone(),
+            symbol_short!("balance"),
+            vec![&env, deployee_address.into_val(&env)],
+        ),
+    ]);
+
+    assert_eq!(results.len(), 2);
+    assert_eq!(i128::from_val(&env, &results.get(1).unwrap()), 70);
+    assert_eq!(token::Client::new(&env, &token).balance(&recipient), 30);
+
+    // Session restrictions apply to each inner call
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+    let signature = Signature::Ed25519(Ed25519Signature {
+        id: session_id,
+        signature: BytesN::from_array(
+            &env,
+            &session_key.sign(&signature_payload.to_array()).to_bytes(),
+        ),
+    });
+    let exec = |calls: Vec<(Address, Symbol, Vec<Val>)>| {
+        let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+            &deployee_address,
+            &signature_payload,
+            vec![&env, signature.clone()].into_val(&env),
+            &vec![
+                &env,
+                Context::Contract(ContractContext {
+                    contract: deployee_address.clone(),
+                    fn_name: symbol_short!("exec"),
+                    args: vec![&env, calls.into_val(&env)],
+                }),
+            ],
+        );
+
+        result
+    };
+
+    assert_eq!(
+        exec(vec![&env, transfer(60), transfer(60)]),
+        Err(Ok(Error::SpendLimitExceeded))
+    );
+    assert_eq!(
+        exec(vec![
+            &env,
+            transfer(10),
+            (
+                Address::generate(&env),
+                symbol_short!("transfer"),
+                vec![&env]
+            )
+        ]),
+        Err(Ok(Error::NotPermitted))
+    );
+    assert_eq!(exec(vec![&env, transfer(60), transfer(40)]), Ok(()));
+}
+
+#[test]
+fn test_is_valid_signature() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let admin_key = p256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+    let admin_id = Bytes::from_array(&env, &[1; 20]);
+    let session_key = SigningKey::from_bytes(&[2; 32]);
+    let session_id = Bytes::from_array(&env, &[2; 20]);
+    let address_id = Bytes::from_array(&env, &[3; 20]);
+
+    env.mock_all_auths();
+
+    deployee_client.init(
+        &admin_id,
+        &SignerKey::Secp256r1(secp256r1_pk(&env, &admin_key)),
+        &Some(rp_id_hash(&env, RP_ID)),
+    );
+    deployee_client.add(
+        &session_id,
+        &SignerKey::Ed25519(BytesN::from_array(
+            &env,
+            &session_key.verifying_key().to_bytes(),
+        )),
+        &false,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+    deployee_client.add(
+        &address_id,
+        &SignerKey::Address(Address::generate(&env)),
+        &false,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+
+    let hash = BytesN::from_array(&env, &[1; 32]);
+    let signature = sign_secp256r1(
+        &env,
+        &admin_id,
+        &admin_key,
+        &hash,
+        authenticator_data(&env, RP_ID, 0x05, 1),
+    );
+
+    // Checking leaves no trace, so the same signature checks out again
+    assert!(deployee_client.is_valid_signature(&hash, &signature));
+    assert!(deployee_client.is_valid_signature(&hash, &signature));
+    assert_eq!(deployee_client.get_signer(&admin_id).last_used, 0);
+
+    // Signed for some other message
+    assert!(!deployee_client.is_valid_signature(&BytesN::from_array(&env, &[2; 32]), &signature));
+
+    let session_signature = Signature::Ed25519(Ed25519Signature {
+        id: session_id.clone(),
+        signature: BytesN::from_array(&env, &session_key.sign(&hash.to_array()).to_bytes()),
+    });
+
+    assert!(deployee_client.is_valid_signature(&hash, &session_signature));
+
+    assert!(!deployee_client.is_valid_signature(
+        &hash,
+        &Signature::Address(AddressSignature { id: address_id })
+    ));
+    assert!(!deployee_client.is_valid_signature(
+        &hash,
+        &Signature::Ed25519(Ed25519Signature {
+            id: Bytes::from_array(&env, &[4; 20]),
+            signature: BytesN::from_array(&env, &[0; 64]),
+        })
+    ));
+
+    // Restricted session signers don't speak for the wallet
+    deployee_client.set_scope(
+        &session_id,
+        &map![&env, (Address::generate(&env), vec![&env])],
+    );
+
+    assert!(!deployee_client.is_valid_signature(&hash, &session_signature));
+
+    // Signers sum their weights towards the default threshold, as they would to authorize
+    let other_admin_key = SigningKey::from_bytes(&[5; 32]);
+    let other_admin_id = Bytes::from_array(&env, &[5; 20]);
+
+    deployee_client.add(
+        &other_admin_id,
+        &SignerKey::Ed25519(BytesN::from_array(
+            &env,
+            &other_admin_key.verifying_key().to_bytes(),
+        )),
+        &true,
+        &1,
+        &None,
+        &None,
+        &None,
+    );
+    deployee_client.set_thresholds(&Thresholds {
+        admin: 1,
+        transfer: 1,
+        default: 2,
+    });
+
+    let other_admin_signature = Signature::Ed25519(Ed25519Signature {
+        id: other_admin_id,
+        signature: BytesN::from_array(&env, &other_admin_key.sign(&hash.to_array()).to_bytes()),
+    });
+
+    assert!(!deployee_client.is_valid_signature(&hash, &signature));
+    assert!(deployee_client
+        .is_valid_signatures(&hash, &vec![&env, signature.clone(), other_admin_signature]));
+    assert!(!deployee_client.is_valid_signatures(&hash, &vec![&env, signature.clone(), signature]));
+}
+
+// Host crypto failures abort natively registered contracts, so this needs the built wallet
+#[test]
+fn test_is_valid_signature_forged() {
+    let env = Env::default();
+
+    env.budget().reset_unlimited();
+
+    let deployee_address = env.register_contract_wasm(None, passkey::WASM);
+    let deployee_client = passkey::Client::new(&env, &deployee_address);
+
+    let admin_key = SigningKey::from_bytes(&[1; 32]);
+    let admin_id = Bytes::from_array(&env, &[1; 20]);
+    let hash = BytesN::from_array(&env, &[1; 32]);
+
+    env.mock_all_auths();
+
+    deployee_client.init(
+        &admin_id,
+        &passkey::SignerKey::Ed25519(BytesN::from_array(
+            &env,
+            &admin_key.verifying_key().to_bytes(),
+        )),
+        &None,
+    );
+    assert!(deployee_client.is_valid_signature(
+        &hash,
+        &passkey::Signature::Ed25519(passkey::Ed25519Signature {
+            id: admin_id.clone(),
+            signature: BytesN::from_array(&env, &admin_key.sign(&hash.to_array()).to_bytes()),
+        }),
+    ));
+
+    assert!(deployee_client
+        .try_is_valid_signature(
+            &hash,
+            &passkey::Signature::Ed25519(passkey::Ed25519Signature {
+                id: admin_id,
+                signature: BytesN::from_array(&env, &admin_key.sign(&[2; 32]).to_bytes()),
+            }),
+        )
+        .is_err());
+}
+
+// Sorted like `merkle_root` does
+fn hash_pair(env: &Env, a: &BytesN<32>, b: &BytesN<32>) -> BytesN<32> {
+    let (left, right) = if a < b { (a, b) } else { (b, a) };
+    let mut pair: Bytes = left.clone().into();
+
+    pair.append(&right.clone().into());
+    env.crypto().sha256(&pair).to_bytes()
+}
+
+#[test]
+fn test_batch_signature() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let admin_key = p256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+    let admin_id = Bytes::from_array(&env, &[1; 20]);
+
+    deployee_client.init(
+        &admin_id,
+        &SignerKey::Secp256r1(secp256r1_pk(&env, &admin_key)),
+        &None,
+    );
+
+    let payloads = [1, 2, 3, 4].map(|i| BytesN::from_array(&env, &[i; 32]));
+    let left = hash_pair(&env, &payloads[0], &payloads[1]);
+    let right = hash_pair(&env, &payloads[2], &payloads[3]);
+    let root = hash_pair(&env, &left, &right);
+
+    let sign = |root: &BytesN<32>, sign_count| {
+        let Signature::Secp256r1(signature) = sign_secp256r1(
+            &env,
+            &admin_id,
+            &admin_key,
+            root,
+            authenticator_data(&env, RP_ID, 0x05, sign_count),
+        ) else {
+            unreachable!()
+        };
+
+        signature
+    };
+    let check_auth = |signature_payload: &BytesN<32>, signature, proof| {
+        let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+            &deployee_address,
+            signature_payload,
+            vec![
+                &env,
+                Signature::Secp256r1Batch(Secp256r1BatchSignature { signature, proof }),
+            ]
+            .into_val(&env),
+            &vec![&env],
+        );
+
+        result
+    };
+
+    let signature = sign(&root, 1);
+
+    // One signature over the root covers every payload in the batch, counter and all
+    assert_eq!(
+        check_auth(
+            &payloads[0],
+            signature.clone(),
+            vec![&env, payloads[1].clone(), right.clone()]
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        check_auth(
+            &payloads[3],
+            signature.clone(),
+            vec![&env, payloads[2].clone(), left.clone()]
+        ),
+        Ok(())
+    );
+
+    // But only with a proof leading to the signed root
+    assert_eq!(
+        check_auth(
+            &payloads[0],
+            signature.clone(),
+            vec![&env, payloads[2].clone(), left.clone()]
+        ),
+        Err(Ok(Error::ClientDataJsonChallengeIncorrect))
+    );
+    assert_eq!(
+        check_auth(
+            &BytesN::from_array(&env, &[5; 32]),
+            signature,
+            vec![&env, payloads[1].clone(), right.clone()]
+        ),
+        Err(Ok(Error::ClientDataJsonChallengeIncorrect))
+    );
+
+    // A different root must come with a higher counter
+    let other_root = hash_pair(&env, &payloads[0], &payloads[1]);
+
+    assert_eq!(
+        check_auth(
+            &payloads[0],
+            sign(&other_root, 1),
+            vec![&env, payloads[1].clone()]
+        ),
+        Err(Ok(Error::SignCountInvalid))
+    );
+    assert_eq!(
+        check_auth(
+            &payloads[0],
+            sign(&other_root, 2),
+            vec![&env, payloads[1].clone()]
+        ),
+        Ok(())
+    );
+}
+
+#[test]
+fn test_delegation() {
+    let env = Env::default();
+    let deployee_address = env.register_contract(None, Contract);
+    let deployee_client = ContractClient::new(&env, &deployee_address);
+
+    let admin_key = p256::ecdsa::SigningKey::from_slice(&[1; 32]).unwrap();
+    let admin_id = Bytes::from_array(&env, &[1; 20]);
+    let ephemeral_key = SigningKey::from_bytes(&[2; 32]);
+    let game = Address::generate(&env);
+
+    deployee_client.init(
+        &admin_id,
+        &SignerKey::Secp256r1(secp256r1_pk(&env, &admin_key)),
+        &None,
+    );
+
+    let delegation = Delegation {
+        key: BytesN::from_array(&env, &ephemeral_key.verifying_key().to_bytes()),
+        epoch: 0,
+        valid_after: env.ledger().sequence(),
+        valid_until: env.ledger().sequence() + 100,
+        scope: map![&env, (game.clone(), vec![&env])],
+    };
+    let certify = |delegation: &Delegation| {
+        let challenge = env
+            .crypto()
+            .sha256(&(deployee_address.clone(), delegation.clone()).to_xdr(&env))
+            .to_bytes();
+        let Signature::Secp256r1(certificate) = sign_secp256r1(
+            &env,
+            &admin_id,
+            &admin_key,
+            &challenge,
+            authenticator_data(&env, RP_ID, 0x05, 1),
+        ) else {
+            unreachable!()
+        };
+
+        certificate
+    };
+    let certificate = certify(&delegation);
+
+    let signature_payload = BytesN::from_array(&env, &[1; 32]);
+    let check_auth = |delegation: &Delegation, certificate: &Secp256r1Signature, context| {
+        let signature = Signature::Delegated(DelegatedSignature {
+            delegation: delegation.clone(),
+            certificate: certificate.clone(),
+            signature: BytesN::from_array(
+                &env,
+                &ephemeral_key.sign(&signature_payload.to_array()).to_bytes(),
+            ),
+        });
+        let result: Result<(), Result<Error, _>> = env.try_invoke_contract_check_auth(
+            &deployee_address,
+            &signature_payload,
+            vec![&env, signature].into_val(&env),
+            &vec![&env, context],
+        );
+
+        result
+    };
+    let call = |contract: &Address, fn_name: Symbol, args: Vec<Val>| {
+        Context::Contract(ContractContext {
+            contract: contract.clone(),
+            fn_name,
+            args,
+        })
+    };
+
+    // The same certificate is good for any number of auths
+    assert_eq!(
+        check_auth(
+            &delegation,
+            &certificate,
+            call(&game, symbol_short!("play"), vec![&env])
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        check_auth(
+            &delegation,
+            &certificate,
+            call(&game, symbol_short!("play"), vec![&env])
+        ),
+        Ok(())
+    );
+
+    // Within its scope only, and never on the wallet itself
+    assert_eq!(
+        check_auth(
+            &delegation,
+            &certificate,
+            call(&Address::generate(&env), symbol_short!("play"), vec![&env])
+        ),
+        Err(Ok(Error::NotPermitted))
+    );
+    assert_eq!(
+        check_auth(
+            &delegation,
+            &certificate,
+            call(
+                &deployee_address,
+                symbol_short!("remove"),
+                vec![&env, admin_id.into_val(&env)]
+            )
+        ),
+        Err(Ok(Error::NotPermitted))
+    );
+
+    // The certificate covers exactly the delegation it was made for
+    let widened = Delegation {
+        scope: map![&env],
+        ..delegation.clone()
+    };
+
+    assert_eq!(
+        check_auth(
+            &widened,
+            &certificate,
+            call(&game, symbol_short!("play"), vec![&env])
+        ),
+        Err(Ok(Error::ClientDataJsonChallengeIncorrect))
+    );
+
+    // Transfers must be allowed by name, not just by contract
+    let token = Address::generate(&env);
+    let transfer = call(
+        &token,
+        symbol_short!("transfer"),
+        vec![
+            &env,
+            deployee_address.into_val(&env),
+            Address::generate(&env).into_val(&env),
+            1i128.into_val(&env),
+        ],
+    );
+
+    for scope in [
+        map![&env],
+        map![&env, (token.clone(), vec![&env])],
+        map![&env, (token.clone(), vec![&env, symbol_short!("approve")])],
+    ] {
+        let delegation = Delegation {
+            scope,
+            ..delegation.clone()
+        };
+
+        assert_eq!(
+            check_auth(&delegation, &certify(&delegation), transfer.clone()),
+            Err(Ok(Error::NotPermitted))
+        );
+    }
+
+    let spender = Delegation {
+        scope: map![&env, (token.clone(), vec![&env, symbol_short!("transfer")])],
+        ..delegation.clone()
+    };
+
+    assert_eq!(
+        check_auth(&spender, &certify(&spender), transfer.clone()),
+        Ok(())
+    );
+
+    // Certificates can't be made to last more than a week
+    let lasting = Delegation {
+        valid_until: env.ledger().sequence() + 60 * 60 * 24 / 5 * 7 + 1,
+        ..delegation.clone()
+    };
+
+    assert_eq!(
+        check_auth(
+            &lasting,
+            &certify(&lasting),
+            call(&game, symbol_short!("play"), vec![&env])
+        ),
+        Err(Ok(Error::SignerExpired))
+    );
+
+    // Nor be used before they're valid
+    let early = Delegation {
+        valid_after: env.ledger().sequence() + 1,
+        ..delegation.clone()
+    };
+
+    assert_eq!(
+        check_auth(
+            &early,
+            &certify(&early),
+            call(&game, symbol_short!("play"), vec![&env])
+        ),
+        Err(Ok(Error::SignerNotYetValid))
+    );
+
+    // The week runs from when they're valid, not from the current ledger
+    let stale = Delegation {
+        valid_after: 0,
+        valid_until: env.ledger().sequence() + 1,
+        ..delegation.clone()
+    };
+
+    env.ledger()
+        .with_mut(|ledger| ledger.sequence_number += 60 * 60 * 24 / 5 * 7);
+
+    assert_eq!(
+        check_auth(
+            &stale,
+            &certify(&stale),
+            call(&game, symbol_short!("play"), vec![&env])
+        ),
+        Err(Ok(Error::SignerExpired))
+    );
+
+    env.ledger()
+        .with_mut(|ledger| ledger.sequence_number -= 60 * 60 * 24 / 5 * 7);
+
+    // Revoking invalidates every certificate made so far, but not those made after
+    env.mock_all_auths();
+
+    deployee_client.revoke_delegations(&admin_id);
+
+    assert_eq!(
+        check_auth(
+            &delegation,
+            &certificate,
+            call(&game, symbol_short!("play"), vec![&env])
+        ),
+        Err(Ok(Error::SignerExpired))
+    );
+
+    let delegation = Delegation {
+        epoch: 1,
+        ..delegation.clone()
+    };
+    let certificate = certify(&delegation);
+
+    assert_eq!(
+        check_auth(
+            &delegation,
+            &certificate,
+            call(&game, symbol_short!("play"), vec![&env])
+        ),
+        Ok(())
+    );
+    assert_eq!(
+        deployee_client.try_revoke_delegations(&Bytes::from_array(&env, &[3; 20])),
+        Err(Ok(Error::NotFound))
+    );
+
+    // And lapses with it
+    env.ledger()
+        .with_mut(|ledger| ledger.sequence_number += 101);
+
+    assert_eq!(
+        check_auth(
+            &delegation,
+            &certificate,
+            call(&game, symbol_short!("play"), vec![&env])
+        ),
+        Err(Ok(Error::SignerExpired))
+    );
+
+    let hash = BytesN::from_array(&env, &[1; 32]);
+
+    // Delegated keys don't speak for the wallet off-chain either
+    assert!(!deployee_client.is_valid_signature(
+        &hash,
+        &Signature::Delegated(DelegatedSignature {
+            delegation: Delegation {
+                valid_until: env.ledger().sequence(),
+                ..delegation.clone()
+            },
+            certificate: certificate.clone(),
+            signature: BytesN::from_array(&env, &ephemeral_key.sign(&hash.to_array()).to_bytes()),
+        })
+    ));
+}
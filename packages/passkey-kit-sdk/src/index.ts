@@ -6,6 +6,11 @@ import {
   Result,
   Spec as ContractSpec,
 } from '@stellar/stellar-sdk/contract';
+import type {
+  u32,
+  i128,
+  Option,
+} from '@stellar/stellar-sdk/contract';
 
 if (typeof window !== 'undefined') {
   //@ts-ignore Buffer exists
@@ -26,21 +31,151 @@ export const Errors = {
   4: { message: "Secp256r1PublicKeyParse" },
   5: { message: "Secp256r1SignatureParse" },
   6: { message: "Secp256r1VerifyFailed" },
-  7: { message: "JsonParseError" }
+  7: { message: "JsonParseError" },
+  8: { message: "SignatureKeyMismatch" },
+  9: { message: "SignerDuplicated" },
+  10: { message: "ThresholdNotMet" },
+  11: { message: "InvalidThreshold" },
+  12: { message: "InvalidWeight" },
+  13: { message: "PolicyRejected" },
+  14: { message: "SpendLimitExceeded" },
+  15: { message: "SignerNotYetValid" },
+  16: { message: "SignerExpired" },
+  17: { message: "RecoveryPending" },
+  18: { message: "RecoveryNotReady" },
+  19: { message: "TimelockNotReady" },
+  20: { message: "AuthenticatorDataInvalid" },
+  21: { message: "SignCountInvalid" },
+  22: { message: "RpIdHashMismatch" },
+  23: { message: "UserPresenceRequired" },
+  24: { message: "UserVerificationRequired" },
+  25: { message: "ClientDataJsonTypeIncorrect" },
+  26: { message: "ClientDataJsonOriginNotAllowed" },
+  27: { message: "ClientDataJsonCrossOrigin" },
+  28: { message: "ClientDataJsonTooLong" },
+  29: { message: "LabelTooLong" },
+  30: { message: "AlreadyInitialized" }
+}
+
+export interface Signer {
+  created: u32;
+  key: SignerKey;
+  label: string;
+  limits: Map<string, SpendLimit>;
+  policies: Array<string>;
+  scope: Map<string, Array<string>>;
+  valid_after: u32;
+  valid_until: u32;
+  weight: u32;
+}
+
+export interface Recovery {
+  delay: u32;
+  threshold: u32;
+}
+
+export type Signature = {tag: "Secp256r1", values: readonly [Secp256r1Signature]} | {tag: "Ed25519", values: readonly [Ed25519Signature]} | {tag: "Address", values: readonly [AddressSignature]} | {tag: "Secp256r1Batch", values: readonly [Secp256r1BatchSignature]} | {tag: "Delegated", values: readonly [DelegatedSignature]};
+
+export type SignerKey = {tag: "Secp256r1", values: readonly [Buffer]} | {tag: "Ed25519", values: readonly [Buffer]} | {tag: "Address", values: readonly [string]};
+
+export interface Delegation {
+  epoch: u32;
+  key: Buffer;
+  scope: Map<string, Array<string>>;
+  valid_after: u32;
+  valid_until: u32;
+}
+
+export interface SignerInfo {
+  admin: boolean;
+  id: Buffer;
+  last_used: u32;
+  signer: Signer;
+}
+
+export interface SpendLimit {
+  limit: i128;
+  window: u32;
+}
+
+export interface Thresholds {
+  admin: u32;
+  default: u32;
+  transfer: u32;
+}
+
+export interface AddressSignature {
+  id: Buffer;
+}
+
+export interface Ed25519Signature {
+  id: Buffer;
+  signature: Buffer;
 }
 
-export interface Signature {
+export interface DelegatedSignature {
+  certificate: Secp256r1Signature;
+  delegation: Delegation;
+  signature: Buffer;
+}
+
+export interface Secp256r1Signature {
   authenticator_data: Buffer;
   client_data_json: Buffer;
   id: Buffer;
   signature: Buffer;
 }
 
+export interface Secp256r1BatchSignature {
+  proof: Array<Buffer>;
+  signature: Secp256r1Signature;
+}
+
 export interface Client {
   /**
    * Construct and simulate a add transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
    */
-  add: ({ id, pk, admin }: { id: Buffer, pk: Buffer, admin: boolean }, options?: {
+  add: ({ id, key, admin, weight, valid_after, valid_until, label }: { id: Buffer, key: SignerKey, admin: boolean, weight: u32, valid_after: Option<u32>, valid_until: Option<u32>, label: Option<string> }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<void>>>
+
+  /**
+   * Construct and simulate a exec transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  exec: ({ calls }: { calls: Array<readonly [string, string, Array<any>]> }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<Array<any>>>>
+
+  /**
+   * Construct and simulate a init transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  init: ({ id, key, rp_id_hash }: { id: Buffer, key: SignerKey, rp_id_hash: Option<Buffer> }, options?: {
     /**
      * The fee to pay for the transaction. Default: BASE_FEE
      */
@@ -77,6 +212,26 @@ export interface Client {
     simulate?: boolean;
   }) => Promise<AssembledTransaction<Result<void>>>
 
+  /**
+   * Construct and simulate a rotate transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  rotate: ({ old_id, new_id, new_key }: { old_id: Buffer, new_id: Buffer, new_key: SignerKey }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<void>>>
+
   /**
    * Construct and simulate a update transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
    */
@@ -96,22 +251,524 @@ export interface Client {
      */
     simulate?: boolean;
   }) => Promise<AssembledTransaction<Result<void>>>
+
+  /**
+   * Construct and simulate a migrate transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  migrate: ({ ids }: { ids: Array<Buffer> }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<void>>>
+
+  /**
+   * Construct and simulate a set_scope transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  set_scope: ({ id, scope }: { id: Buffer, scope: Map<string, Array<string>> }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<void>>>
+
+  /**
+   * Construct and simulate a get_signer transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  get_signer: ({ id }: { id: Buffer }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<SignerInfo>>>
+
+  /**
+   * Construct and simulate a set_limits transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  set_limits: ({ id, limits }: { id: Buffer, limits: Map<string, SpendLimit> }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<void>>>
+
+  /**
+   * Construct and simulate a admin_count transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  admin_count: (options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<u32>>
+
+  /**
+   * Construct and simulate a set_origins transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  set_origins: ({ origins, cross_origin }: { origins: Array<Buffer>, cross_origin: boolean }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<void>>>
+
+  /**
+   * Construct and simulate a add_guardian transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  add_guardian: ({ id, key }: { id: Buffer, key: SignerKey }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<void>>>
+
+  /**
+   * Construct and simulate a list_signers transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  list_signers: (options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Array<SignerInfo>>>
+
+  /**
+   * Construct and simulate a set_policies transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  set_policies: ({ id, policies }: { id: Buffer, policies: Array<string> }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<void>>>
+
+  /**
+   * Construct and simulate a set_recovery transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  set_recovery: ({ recovery }: { recovery: Recovery }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<void>>>
+
+  /**
+   * Construct and simulate a set_timelock transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  set_timelock: ({ delay }: { delay: u32 }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<void>>>
+
+  /**
+   * Construct and simulate a set_webauthn transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  set_webauthn: ({ rp_id_hash, user_verification }: { rp_id_hash: Option<Buffer>, user_verification: boolean }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<void>>>
+
+  /**
+   * Construct and simulate a cancel_pending transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  cancel_pending: ({ op_id }: { op_id: u32 }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<void>>>
+
+  /**
+   * Construct and simulate a set_thresholds transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  set_thresholds: ({ thresholds }: { thresholds: Thresholds }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<void>>>
+
+  /**
+   * Construct and simulate a start_recovery transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  start_recovery: ({ id, pk }: { id: Buffer, pk: Buffer }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<void>>>
+
+  /**
+   * Construct and simulate a cancel_recovery transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  cancel_recovery: (options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<void>>>
+
+  /**
+   * Construct and simulate a execute_pending transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  execute_pending: ({ op_id }: { op_id: u32 }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<void>>>
+
+  /**
+   * Construct and simulate a finish_recovery transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  finish_recovery: (options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<void>>>
+
+  /**
+   * Construct and simulate a remove_guardian transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  remove_guardian: ({ id }: { id: Buffer }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<void>>>
+
+  /**
+   * Construct and simulate a is_valid_signature transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  is_valid_signature: ({ hash, signature }: { hash: Buffer, signature: Signature }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<boolean>>
+
+  /**
+   * Construct and simulate a revoke_delegations transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  revoke_delegations: ({ id }: { id: Buffer }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<Result<void>>>
+
+  /**
+   * Construct and simulate a is_valid_signatures transaction. Returns an `AssembledTransaction` object which will have a `result` field containing the result of the simulation. If this transaction changes contract state, you will need to call `signAndSend()` on the returned object.
+   */
+  is_valid_signatures: ({ hash, signatures }: { hash: Buffer, signatures: Array<Signature> }, options?: {
+    /**
+     * The fee to pay for the transaction. Default: BASE_FEE
+     */
+    fee?: number;
+
+    /**
+     * The maximum amount of time to wait for the transaction to complete. Default: DEFAULT_TIMEOUT
+     */
+    timeoutInSeconds?: number;
+
+    /**
+     * Whether to automatically simulate the transaction when constructing the AssembledTransaction. Default: true
+     */
+    simulate?: boolean;
+  }) => Promise<AssembledTransaction<boolean>>
 }
 export class Client extends ContractClient {
   constructor(public readonly options: ContractClientOptions) {
     super(
-      new ContractSpec(["AAAABAAAAAAAAAAAAAAABUVycm9yAAAAAAAABwAAAAAAAAAITm90Rm91bmQAAAABAAAAAAAAAAxOb3RQZXJtaXR0ZWQAAAACAAAAAAAAACBDbGllbnREYXRhSnNvbkNoYWxsZW5nZUluY29ycmVjdAAAAAMAAAAAAAAAF1NlY3AyNTZyMVB1YmxpY0tleVBhcnNlAAAAAAQAAAAAAAAAF1NlY3AyNTZyMVNpZ25hdHVyZVBhcnNlAAAAAAUAAAAAAAAAFVNlY3AyNTZyMVZlcmlmeUZhaWxlZAAAAAAAAAYAAAAAAAAADkpzb25QYXJzZUVycm9yAAAAAAAH",
-        "AAAAAAAAAAAAAAADYWRkAAAAAAMAAAAAAAAAAmlkAAAAAAAOAAAAAAAAAAJwawAAAAAD7gAAAEEAAAAAAAAABWFkbWluAAAAAAAAAQAAAAEAAAPpAAAD7QAAAAAAAAAD",
+      new ContractSpec(["AAAAAAAAAAAAAAADYWRkAAAAAAcAAAAAAAAAAmlkAAAAAAAOAAAAAAAAAANrZXkAAAAH0AAAAAlTaWduZXJLZXkAAAAAAAAAAAAABWFkbWluAAAAAAAAAQAAAAAAAAAGd2VpZ2h0AAAAAAAEAAAAAAAAAAt2YWxpZF9hZnRlcgAAAAPoAAAABAAAAAAAAAALdmFsaWRfdW50aWwAAAAD6AAAAAQAAAAAAAAABWxhYmVsAAAAAAAD6AAAABAAAAABAAAD6QAAA+0AAAAAAAAAAw==",
+        "AAAAAAAAAAAAAAAEZXhlYwAAAAEAAAAAAAAABWNhbGxzAAAAAAAD6gAAA+0AAAADAAAAEwAAABEAAAPqAAAAAAAAAAEAAAPpAAAD6gAAAAAAAAAD",
+        "AAAAAAAAAAAAAAAEaW5pdAAAAAMAAAAAAAAAAmlkAAAAAAAOAAAAAAAAAANrZXkAAAAH0AAAAAlTaWduZXJLZXkAAAAAAAAAAAAACnJwX2lkX2hhc2gAAAAAA+gAAAPuAAAAIAAAAAEAAAPpAAAD7QAAAAAAAAAD",
         "AAAAAAAAAAAAAAAGcmVtb3ZlAAAAAAABAAAAAAAAAAJpZAAAAAAADgAAAAEAAAPpAAAD7QAAAAAAAAAD",
+        "AAAAAAAAAAAAAAAGcm90YXRlAAAAAAADAAAAAAAAAAZvbGRfaWQAAAAAAA4AAAAAAAAABm5ld19pZAAAAAAADgAAAAAAAAAHbmV3X2tleQAAAAfQAAAACVNpZ25lcktleQAAAAAAAAEAAAPpAAAD7QAAAAAAAAAD",
         "AAAAAAAAAAAAAAAGdXBkYXRlAAAAAAABAAAAAAAAAARoYXNoAAAD7gAAACAAAAABAAAD6QAAA+0AAAAAAAAAAw==",
-        "AAAAAQAAAAAAAAAAAAAACVNpZ25hdHVyZQAAAAAAAAQAAAAAAAAAEmF1dGhlbnRpY2F0b3JfZGF0YQAAAAAADgAAAAAAAAAQY2xpZW50X2RhdGFfanNvbgAAAA4AAAAAAAAAAmlkAAAAAAAOAAAAAAAAAAlzaWduYXR1cmUAAAAAAAPuAAAAQA==",
-        "AAAAAAAAAAAAAAAMX19jaGVja19hdXRoAAAAAwAAAAAAAAARc2lnbmF0dXJlX3BheWxvYWQAAAAAAAPuAAAAIAAAAAAAAAAJc2lnbmF0dXJlAAAAAAAH0AAAAAlTaWduYXR1cmUAAAAAAAAAAAAADWF1dGhfY29udGV4dHMAAAAAAAPqAAAH0AAAAAdDb250ZXh0AAAAAAEAAAPpAAAD7QAAAAAAAAAD"]),
+        "AAAAAAAAAAAAAAAHbWlncmF0ZQAAAAABAAAAAAAAAANpZHMAAAAD6gAAAA4AAAABAAAD6QAAA+0AAAAAAAAAAw==",
+        "AAAABAAAAAAAAAAAAAAABUVycm9yAAAAAAAAHgAAAAAAAAAITm90Rm91bmQAAAABAAAAAAAAAAxOb3RQZXJtaXR0ZWQAAAACAAAAAAAAACBDbGllbnREYXRhSnNvbkNoYWxsZW5nZUluY29ycmVjdAAAAAMAAAAAAAAAF1NlY3AyNTZyMVB1YmxpY0tleVBhcnNlAAAAAAQAAAAAAAAAF1NlY3AyNTZyMVNpZ25hdHVyZVBhcnNlAAAAAAUAAAAAAAAAFVNlY3AyNTZyMVZlcmlmeUZhaWxlZAAAAAAAAAYAAAAAAAAADkpzb25QYXJzZUVycm9yAAAAAAAHAAAAAAAAABRTaWduYXR1cmVLZXlNaXNtYXRjaAAAAAgAAAAAAAAAEFNpZ25lckR1cGxpY2F0ZWQAAAAJAAAAAAAAAA9UaHJlc2hvbGROb3RNZXQAAAAACgAAAAAAAAAQSW52YWxpZFRocmVzaG9sZAAAAAsAAAAAAAAADUludmFsaWRXZWlnaHQAAAAAAAAMAAAAAAAAAA5Qb2xpY3lSZWplY3RlZAAAAAAADQAAAAAAAAASU3BlbmRMaW1pdEV4Y2VlZGVkAAAAAAAOAAAAAAAAABFTaWduZXJOb3RZZXRWYWxpZAAAAAAAAA8AAAAAAAAADVNpZ25lckV4cGlyZWQAAAAAAAAQAAAAAAAAAA9SZWNvdmVyeVBlbmRpbmcAAAAAEQAAAAAAAAAQUmVjb3ZlcnlOb3RSZWFkeQAAABIAAAAAAAAAEFRpbWVsb2NrTm90UmVhZHkAAAATAAAAAAAAABhBdXRoZW50aWNhdG9yRGF0YUludmFsaWQAAAAUAAAAAAAAABBTaWduQ291bnRJbnZhbGlkAAAAFQAAAAAAAAAQUnBJZEhhc2hNaXNtYXRjaAAAABYAAAAAAAAAFFVzZXJQcmVzZW5jZVJlcXVpcmVkAAAAFwAAAAAAAAAYVXNlclZlcmlmaWNhdGlvblJlcXVpcmVkAAAAGAAAAAAAAAAbQ2xpZW50RGF0YUpzb25UeXBlSW5jb3JyZWN0AAAAABkAAAAAAAAAHkNsaWVudERhdGFKc29uT3JpZ2luTm90QWxsb3dlZAAAAAAAGgAAAAAAAAAZQ2xpZW50RGF0YUpzb25Dcm9zc09yaWdpbgAAAAAAABsAAAAAAAAAFUNsaWVudERhdGFKc29uVG9vTG9uZwAAAAAAABwAAAAAAAAADExhYmVsVG9vTG9uZwAAAB0AAAAAAAAAEkFscmVhZHlJbml0aWFsaXplZAAAAAAAHg==",
+        "AAAAAQAAAAAAAAAAAAAABlNpZ25lcgAAAAAACQAAAAAAAAAHY3JlYXRlZAAAAAAEAAAAAAAAAANrZXkAAAAH0AAAAAlTaWduZXJLZXkAAAAAAAAAAAAABWxhYmVsAAAAAAAAEAAAAAAAAAAGbGltaXRzAAAAAAPsAAAAEwAAB9AAAAAKU3BlbmRMaW1pdAAAAAAAAAAAAAhwb2xpY2llcwAAA+oAAAATAAAAAAAAAAVzY29wZQAAAAAAA+wAAAATAAAD6gAAABEAAAAAAAAAC3ZhbGlkX2FmdGVyAAAAAAQAAAAAAAAAC3ZhbGlkX3VudGlsAAAAAAQAAAAAAAAABndlaWdodAAAAAAABA==",
+        "AAAAAAAAAAAAAAAJc2V0X3Njb3BlAAAAAAAAAgAAAAAAAAACaWQAAAAAAA4AAAAAAAAABXNjb3BlAAAAAAAD7AAAABMAAAPqAAAAEQAAAAEAAAPpAAAD7QAAAAAAAAAD",
+        "AAAAAAAAAAAAAAAKZ2V0X3NpZ25lcgAAAAAAAQAAAAAAAAACaWQAAAAAAA4AAAABAAAD6QAAB9AAAAAKU2lnbmVySW5mbwAAAAAAAw==",
+        "AAAAAAAAAAAAAAAKc2V0X2xpbWl0cwAAAAAAAgAAAAAAAAACaWQAAAAAAA4AAAAAAAAABmxpbWl0cwAAAAAD7AAAABMAAAfQAAAAClNwZW5kTGltaXQAAAAAAAEAAAPpAAAD7QAAAAAAAAAD",
+        "AAAAAQAAAAAAAAAAAAAACFJlY292ZXJ5AAAAAgAAAAAAAAAFZGVsYXkAAAAAAAAEAAAAAAAAAAl0aHJlc2hvbGQAAAAAAAAE",
+        "AAAAAAAAAAAAAAALYWRtaW5fY291bnQAAAAAAAAAAAEAAAAE",
+        "AAAAAAAAAAAAAAALc2V0X29yaWdpbnMAAAAAAgAAAAAAAAAHb3JpZ2lucwAAAAPqAAAADgAAAAAAAAAMY3Jvc3Nfb3JpZ2luAAAAAQAAAAEAAAPpAAAD7QAAAAAAAAAD",
+        "AAAAAgAAAAAAAAAAAAAACVNpZ25hdHVyZQAAAAAAAAUAAAABAAAAAAAAAAlTZWNwMjU2cjEAAAAAAAABAAAH0AAAABJTZWNwMjU2cjFTaWduYXR1cmUAAAAAAAEAAAAAAAAAB0VkMjU1MTkAAAAAAQAAB9AAAAAQRWQyNTUxOVNpZ25hdHVyZQAAAAEAAAAAAAAAB0FkZHJlc3MAAAAAAQAAB9AAAAAQQWRkcmVzc1NpZ25hdHVyZQAAAAEAAAAAAAAADlNlY3AyNTZyMUJhdGNoAAAAAAABAAAH0AAAABdTZWNwMjU2cjFCYXRjaFNpZ25hdHVyZQAAAAABAAAAAAAAAAlEZWxlZ2F0ZWQAAAAAAAABAAAH0AAAABJEZWxlZ2F0ZWRTaWduYXR1cmUAAA==",
+        "AAAAAgAAAAAAAAAAAAAACVNpZ25lcktleQAAAAAAAAMAAAABAAAAAAAAAAlTZWNwMjU2cjEAAAAAAAABAAAD7gAAAEEAAAABAAAAAAAAAAdFZDI1NTE5AAAAAAEAAAPuAAAAIAAAAAEAAAAAAAAAB0FkZHJlc3MAAAAAAQAAABM=",
+        "AAAAAAAAAAAAAAAMYWRkX2d1YXJkaWFuAAAAAgAAAAAAAAACaWQAAAAAAA4AAAAAAAAAA2tleQAAAAfQAAAACVNpZ25lcktleQAAAAAAAAEAAAPpAAAD7QAAAAAAAAAD",
+        "AAAAAAAAAAAAAAAMbGlzdF9zaWduZXJzAAAAAAAAAAEAAAPqAAAH0AAAAApTaWduZXJJbmZvAAA=",
+        "AAAAAAAAAAAAAAAMc2V0X3BvbGljaWVzAAAAAgAAAAAAAAACaWQAAAAAAA4AAAAAAAAACHBvbGljaWVzAAAD6gAAABMAAAABAAAD6QAAA+0AAAAAAAAAAw==",
+        "AAAAAAAAAAAAAAAMc2V0X3JlY292ZXJ5AAAAAQAAAAAAAAAIcmVjb3ZlcnkAAAfQAAAACFJlY292ZXJ5AAAAAQAAA+kAAAPtAAAAAAAAAAM=",
+        "AAAAAAAAAAAAAAAMc2V0X3RpbWVsb2NrAAAAAQAAAAAAAAAFZGVsYXkAAAAAAAAEAAAAAQAAA+kAAAPtAAAAAAAAAAM=",
+        "AAAAAAAAAAAAAAAMc2V0X3dlYmF1dGhuAAAAAgAAAAAAAAAKcnBfaWRfaGFzaAAAAAAD6AAAA+4AAAAgAAAAAAAAABF1c2VyX3ZlcmlmaWNhdGlvbgAAAAAAAAEAAAABAAAD6QAAA+0AAAAAAAAAAw==",
+        "AAAAAAAAAAAAAAAMX19jaGVja19hdXRoAAAAAwAAAAAAAAARc2lnbmF0dXJlX3BheWxvYWQAAAAAAAPuAAAAIAAAAAAAAAAKc2lnbmF0dXJlcwAAAAAD6gAAB9AAAAAJU2lnbmF0dXJlAAAAAAAAAAAAAA1hdXRoX2NvbnRleHRzAAAAAAAD6gAAB9AAAAAHQ29udGV4dAAAAAABAAAD6QAAA+0AAAAAAAAAAw==",
+        "AAAAAQAAAAAAAAAAAAAACkRlbGVnYXRpb24AAAAAAAUAAAAAAAAABWVwb2NoAAAAAAAABAAAAAAAAAADa2V5AAAAA+4AAAAgAAAAAAAAAAVzY29wZQAAAAAAA+wAAAATAAAD6gAAABEAAAAAAAAAC3ZhbGlkX2FmdGVyAAAAAAQAAAAAAAAAC3ZhbGlkX3VudGlsAAAAAAQ=",
+        "AAAAAQAAAAAAAAAAAAAAClNpZ25lckluZm8AAAAAAAQAAAAAAAAABWFkbWluAAAAAAAAAQAAAAAAAAACaWQAAAAAAA4AAAAAAAAACWxhc3RfdXNlZAAAAAAAAAQAAAAAAAAABnNpZ25lcgAAAAAH0AAAAAZTaWduZXIAAA==",
+        "AAAAAQAAAAAAAAAAAAAAClNwZW5kTGltaXQAAAAAAAIAAAAAAAAABWxpbWl0AAAAAAAACwAAAAAAAAAGd2luZG93AAAAAAAE",
+        "AAAAAQAAAAAAAAAAAAAAClRocmVzaG9sZHMAAAAAAAMAAAAAAAAABWFkbWluAAAAAAAABAAAAAAAAAAHZGVmYXVsdAAAAAAEAAAAAAAAAAh0cmFuc2ZlcgAAAAQ=",
+        "AAAAAAAAAAAAAAAOY2FuY2VsX3BlbmRpbmcAAAAAAAEAAAAAAAAABW9wX2lkAAAAAAAABAAAAAEAAAPpAAAD7QAAAAAAAAAD",
+        "AAAAAAAAAAAAAAAOc2V0X3RocmVzaG9sZHMAAAAAAAEAAAAAAAAACnRocmVzaG9sZHMAAAAAB9AAAAAKVGhyZXNob2xkcwAAAAAAAQAAA+kAAAPtAAAAAAAAAAM=",
+        "AAAAAAAAAAAAAAAOc3RhcnRfcmVjb3ZlcnkAAAAAAAIAAAAAAAAAAmlkAAAAAAAOAAAAAAAAAAJwawAAAAAD7gAAAEEAAAABAAAD6QAAA+0AAAAAAAAAAw==",
+        "AAAAAAAAAAAAAAAPY2FuY2VsX3JlY292ZXJ5AAAAAAAAAAABAAAD6QAAA+0AAAAAAAAAAw==",
+        "AAAAAAAAAAAAAAAPZXhlY3V0ZV9wZW5kaW5nAAAAAAEAAAAAAAAABW9wX2lkAAAAAAAABAAAAAEAAAPpAAAD7QAAAAAAAAAD",
+        "AAAAAAAAAAAAAAAPZmluaXNoX3JlY292ZXJ5AAAAAAAAAAABAAAD6QAAA+0AAAAAAAAAAw==",
+        "AAAAAAAAAAAAAAAPcmVtb3ZlX2d1YXJkaWFuAAAAAAEAAAAAAAAAAmlkAAAAAAAOAAAAAQAAA+kAAAPtAAAAAAAAAAM=",
+        "AAAAAAAAAAAAAAASaXNfdmFsaWRfc2lnbmF0dXJlAAAAAAACAAAAAAAAAARoYXNoAAAD7gAAACAAAAAAAAAACXNpZ25hdHVyZQAAAAAAB9AAAAAJU2lnbmF0dXJlAAAAAAAAAQAAAAE=",
+        "AAAAAAAAAAAAAAAScmV2b2tlX2RlbGVnYXRpb25zAAAAAAABAAAAAAAAAAJpZAAAAAAADgAAAAEAAAPpAAAD7QAAAAAAAAAD",
+        "AAAAAQAAAAAAAAAAAAAAEEFkZHJlc3NTaWduYXR1cmUAAAABAAAAAAAAAAJpZAAAAAAADg==",
+        "AAAAAQAAAAAAAAAAAAAAEEVkMjU1MTlTaWduYXR1cmUAAAACAAAAAAAAAAJpZAAAAAAADgAAAAAAAAAJc2lnbmF0dXJlAAAAAAAD7gAAAEA=",
+        "AAAAAAAAAAAAAAATaXNfdmFsaWRfc2lnbmF0dXJlcwAAAAACAAAAAAAAAARoYXNoAAAD7gAAACAAAAAAAAAACnNpZ25hdHVyZXMAAAAAA+oAAAfQAAAACVNpZ25hdHVyZQAAAAAAAAEAAAAB",
+        "AAAAAQAAAAAAAAAAAAAAEkRlbGVnYXRlZFNpZ25hdHVyZQAAAAAAAwAAAAAAAAALY2VydGlmaWNhdGUAAAAH0AAAABJTZWNwMjU2cjFTaWduYXR1cmUAAAAAAAAAAAAKZGVsZWdhdGlvbgAAAAAH0AAAAApEZWxlZ2F0aW9uAAAAAAAAAAAACXNpZ25hdHVyZQAAAAAAA+4AAABA",
+        "AAAAAQAAAAAAAAAAAAAAElNlY3AyNTZyMVNpZ25hdHVyZQAAAAAABAAAAAAAAAASYXV0aGVudGljYXRvcl9kYXRhAAAAAAAOAAAAAAAAABBjbGllbnRfZGF0YV9qc29uAAAADgAAAAAAAAACaWQAAAAAAA4AAAAAAAAACXNpZ25hdHVyZQAAAAAAAA4=",
+        "AAAAAQAAAAAAAAAAAAAAF1NlY3AyNTZyMUJhdGNoU2lnbmF0dXJlAAAAAAIAAAAAAAAABXByb29mAAAAAAAD6gAAA+4AAAAgAAAAAAAAAAlzaWduYXR1cmUAAAAAAAfQAAAAElNlY3AyNTZyMVNpZ25hdHVyZQAA"]),
       options
     )
   }
   public readonly fromJSON = {
     add: this.txFromJSON<Result<void>>,
+    exec: this.txFromJSON<Result<Array<any>>>,
+    init: this.txFromJSON<Result<void>>,
     remove: this.txFromJSON<Result<void>>,
-    update: this.txFromJSON<Result<void>>
+    rotate: this.txFromJSON<Result<void>>,
+    update: this.txFromJSON<Result<void>>,
+    migrate: this.txFromJSON<Result<void>>,
+    set_scope: this.txFromJSON<Result<void>>,
+    get_signer: this.txFromJSON<Result<SignerInfo>>,
+    set_limits: this.txFromJSON<Result<void>>,
+    admin_count: this.txFromJSON<u32>,
+    set_origins: this.txFromJSON<Result<void>>,
+    add_guardian: this.txFromJSON<Result<void>>,
+    list_signers: this.txFromJSON<Array<SignerInfo>>,
+    set_policies: this.txFromJSON<Result<void>>,
+    set_recovery: this.txFromJSON<Result<void>>,
+    set_timelock: this.txFromJSON<Result<void>>,
+    set_webauthn: this.txFromJSON<Result<void>>,
+    cancel_pending: this.txFromJSON<Result<void>>,
+    set_thresholds: this.txFromJSON<Result<void>>,
+    start_recovery: this.txFromJSON<Result<void>>,
+    cancel_recovery: this.txFromJSON<Result<void>>,
+    execute_pending: this.txFromJSON<Result<void>>,
+    finish_recovery: this.txFromJSON<Result<void>>,
+    remove_guardian: this.txFromJSON<Result<void>>,
+    is_valid_signature: this.txFromJSON<boolean>,
+    revoke_delegations: this.txFromJSON<Result<void>>,
+    is_valid_signatures: this.txFromJSON<boolean>
   }
 }
\ No newline at end of file
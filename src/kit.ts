@@ -223,23 +223,29 @@ export class PasskeyKit extends PasskeyBase {
         );
 
         credentials.signatureExpirationLedger(lastLedger + ledgersToLive)
-        credentials.signature(xdr.ScVal.scvMap([
-            new xdr.ScMapEntry({
-                key: xdr.ScVal.scvSymbol('authenticator_data'),
-                val: xdr.ScVal.scvBytes(base64url.toBuffer(authenticationResponse.response.authenticatorData)),
-            }),
-            new xdr.ScMapEntry({
-                key: xdr.ScVal.scvSymbol('client_data_json'),
-                val: xdr.ScVal.scvBytes(base64url.toBuffer(authenticationResponse.response.clientDataJSON)),
-            }),
-            new xdr.ScMapEntry({
-                key: xdr.ScVal.scvSymbol('id'),
-                val: xdr.ScVal.scvBytes(base64url.toBuffer(authenticationResponse.id)),
-            }),
-            new xdr.ScMapEntry({
-                key: xdr.ScVal.scvSymbol('signature'),
-                val: xdr.ScVal.scvBytes(signature),
-            }),
+        // The wallet takes a `Vec<Signature>`, here a single `Signature::Secp256r1`
+        credentials.signature(xdr.ScVal.scvVec([
+            xdr.ScVal.scvVec([
+                xdr.ScVal.scvSymbol('Secp256r1'),
+                xdr.ScVal.scvMap([
+                    new xdr.ScMapEntry({
+                        key: xdr.ScVal.scvSymbol('authenticator_data'),
+                        val: xdr.ScVal.scvBytes(base64url.toBuffer(authenticationResponse.response.authenticatorData)),
+                    }),
+                    new xdr.ScMapEntry({
+                        key: xdr.ScVal.scvSymbol('client_data_json'),
+                        val: xdr.ScVal.scvBytes(base64url.toBuffer(authenticationResponse.response.clientDataJSON)),
+                    }),
+                    new xdr.ScMapEntry({
+                        key: xdr.ScVal.scvSymbol('id'),
+                        val: xdr.ScVal.scvBytes(base64url.toBuffer(authenticationResponse.id)),
+                    }),
+                    new xdr.ScMapEntry({
+                        key: xdr.ScVal.scvSymbol('signature'),
+                        val: xdr.ScVal.scvBytes(signature),
+                    }),
+                ])
+            ])
         ]))
 
         return entry
@@ -69,8 +69,11 @@ export class PasskeyServer extends PasskeyBase {
 
         for (const signer of signers) {
             if (!signer.admin) {
+                // Session signers live under `DataKey::Signer(id)` in temporary storage, gone once they expire
                 try {
-                    await this.rpc.getContractData(contractId, xdr.ScVal.scvBytes(signer.id), SorobanRpc.Durability.Temporary)
+                    const key = xdr.ScVal.scvVec([xdr.ScVal.scvSymbol('Signer'), xdr.ScVal.scvBytes(signer.id)])
+
+                    await this.rpc.getContractData(contractId, key, SorobanRpc.Durability.Temporary)
                 } catch {
                     signer.expired = true
                 }
@@ -3,7 +3,7 @@ use zephyr_sdk::{
     prelude::*,
     soroban_sdk::{
         xdr::{Hash, PublicKey, ScAddress, ScVal, ScVec, VecM},
-        Bytes, BytesN, Symbol,
+        Bytes, BytesN, String as SorobanString, Symbol,
     },
     DatabaseDerive, EnvClient,
 };
@@ -23,12 +23,30 @@ pub struct AdjacentEvents {
 pub struct Signers {
     address: String,
     id: Vec<u8>,
+    kind: String,
     pk: Vec<u8>,
     date: u64,
     admin: i32,
+    weight: i32,
+    valid_after: Option<u32>,
+    valid_until: Option<u32>,
+    label: String,
+    created: u32,
+    last_used: u32,
     active: i32,
 }
 
+#[derive(DatabaseDerive, Clone, Serialize)]
+#[with_name("recoveries")]
+pub struct Recoveries {
+    address: String,
+    id: Vec<u8>,
+    pk: Vec<u8>,
+    date: u64,
+    execute_after: u32,
+    status: String, // pending, cancelled or finished
+}
+
 fn to_store(existing_addresses: &Vec<String>, topics: &VecM<ScVal>, data: &ScVal) -> Vec<String> {
     let mut addresses: Vec<String> = Vec::new();
 
@@ -116,6 +134,28 @@ fn find_val() {
     assert!(!find_address_in_scval(&scval, [2; 32]));
 }
 
+#[test]
+fn signer_key() {
+    let scval = ScVal::Bytes([4; 65].to_vec().try_into().unwrap());
+    assert_eq!(
+        signer_key_from_scval(&scval),
+        ("Secp256r1".to_string(), [4; 65].to_vec())
+    );
+
+    let scval = ScVal::Vec(Some(ScVec(
+        [
+            ScVal::Symbol("Ed25519".try_into().unwrap()),
+            ScVal::Bytes([2; 32].to_vec().try_into().unwrap()),
+        ]
+        .try_into()
+        .unwrap(),
+    )));
+    assert_eq!(
+        signer_key_from_scval(&scval),
+        ("Ed25519".to_string(), [2; 32].to_vec())
+    );
+}
+
 fn bytes_to_vec(bytes: Bytes) -> Vec<u8> {
     let mut result = Vec::new();
 
@@ -126,14 +166,45 @@ fn bytes_to_vec(bytes: Bytes) -> Vec<u8> {
     result
 }
 
-fn bytesn_to_vec(bytes: BytesN<65>) -> Vec<u8> {
-    let mut result = Vec::new();
+// Labels are capped at 32 bytes by the wallet
+fn string_to_string(string: SorobanString) -> String {
+    let mut buffer = [0u8; 32];
+    let len = (string.len() as usize).min(buffer.len());
 
-    for byte in bytes.iter() {
-        result.push(byte);
+    string.copy_into_slice(&mut buffer[..len]);
+
+    String::from_utf8_lossy(&buffer[..len]).into_owned()
+}
+
+// `SignerKey` is a `#[contracttype]` enum, so it arrives as a `[Symbol, Bytes | Address]` vec.
+// Address keys are stored as their strkey. Wallets still on the WASM from before `SignerKey` send the bare secp256r1 public key.
+fn signer_key_from_scval(val: &ScVal) -> (String, Vec<u8>) {
+    if let ScVal::Bytes(key) = val {
+        return ("Secp256r1".into(), key.to_vec());
     }
 
-    result
+    if let ScVal::Vec(Some(scvec)) = val {
+        match (scvec.0.first(), scvec.0.get(1)) {
+            (Some(ScVal::Symbol(kind)), Some(ScVal::Bytes(key))) => {
+                return (kind.to_string(), key.to_vec());
+            }
+            (Some(ScVal::Symbol(kind)), Some(ScVal::Address(address))) => {
+                let address = match address {
+                    ScAddress::Account(account) => {
+                        let PublicKey::PublicKeyTypeEd25519(pubkey) = &account.0;
+
+                        stellar_strkey::ed25519::PublicKey(pubkey.0).to_string()
+                    }
+                    ScAddress::Contract(hash) => stellar_strkey::Contract(hash.0).to_string(),
+                };
+
+                return (kind.to_string(), address.into_bytes());
+            }
+            _ => {}
+        }
+    }
+
+    panic!("unexpected signer key")
 }
 
 #[no_mangle]
@@ -177,12 +248,32 @@ pub extern "C" fn on_close() {
                             if etype == Symbol::new(env.soroban(), "add") {
                                 let id: Bytes = env.from_scval(&event.topics[2]);
                                 let id = bytes_to_vec(id);
-                                let pk: BytesN<65> = env.from_scval(&event.topics[3]);
-                                let pk = bytesn_to_vec(pk);
+                                let (kind, pk) = signer_key_from_scval(&event.topics[3]);
                                 let date = env.reader().ledger_timestamp();
-                                let admin = env.from_scval::<bool>(&event.data) as i32;
-
-                                // let (pk, admin): (BytesN<65>, bool) = env.from_scval(&event.data);
+                                // Wallets still on the WASM from before signer metadata only say whether it's an admin
+                                let (admin, weight, valid_after, valid_until, label, created): (
+                                    bool,
+                                    u32,
+                                    Option<u32>,
+                                    Option<u32>,
+                                    SorobanString,
+                                    u32,
+                                ) = match env.try_from_scval::<bool>(&event.data) {
+                                    Ok(admin) => (
+                                        admin,
+                                        1,
+                                        None,
+                                        None,
+                                        SorobanString::from_str(env.soroban(), ""),
+                                        0,
+                                    ),
+                                    Err(_) => env.from_scval(&event.data),
+                                };
+                                let admin = admin as i32;
+                                let weight = weight as i32;
+                                let label = string_to_string(label);
+
+                                // let (pk, admin, weight, valid_after, valid_until): (BytesN<65>, bool, u32, Option<u32>, Option<u32>) = env.from_scval(&event.data);
 
                                 let older: Vec<Signers> = env
                                     .read_filter()
@@ -195,9 +286,16 @@ pub extern "C" fn on_close() {
                                         address: stellar_strkey::Contract(event.contract)
                                             .to_string(),
                                         id,
+                                        kind,
                                         pk,
                                         date,
                                         admin,
+                                        weight,
+                                        valid_after,
+                                        valid_until,
+                                        label,
+                                        created,
+                                        last_used: 0,
                                         active: 1,
                                     };
 
@@ -206,9 +304,15 @@ pub extern "C" fn on_close() {
                                     let mut older = older[0].clone();
 
                                     older.active = 1;
+                                    older.kind = kind;
                                     older.pk = pk;
                                     older.date = date;
                                     older.admin = admin;
+                                    older.weight = weight;
+                                    older.valid_after = valid_after;
+                                    older.valid_until = valid_until;
+                                    older.label = label;
+                                    older.created = created;
 
                                     env.update()
                                         .column_equal_to("id", id)
@@ -231,6 +335,89 @@ pub extern "C" fn on_close() {
                                     .column_equal_to("id", id)
                                     .execute(&older)
                                     .unwrap();
+                            } else if etype == Symbol::new(env.soroban(), "used") {
+                                let id: Bytes = env.from_scval(&event.topics[2]);
+                                let id = bytes_to_vec(id);
+                                let last_used: u32 = env.from_scval(&event.data);
+                                let older: Vec<Signers> = env
+                                    .read_filter()
+                                    .column_equal_to("id", id.clone())
+                                    .read()
+                                    .unwrap();
+
+                                if let Some(older) = older.first() {
+                                    let mut older = older.clone();
+
+                                    older.last_used = last_used;
+
+                                    env.update()
+                                        .column_equal_to("id", id)
+                                        .execute(&older)
+                                        .unwrap();
+                                }
+                            } else if etype == Symbol::new(env.soroban(), "rotate") {
+                                let id: Bytes = env.from_scval(&event.topics[2]);
+                                let id = bytes_to_vec(id);
+                                let (kind, pk) = signer_key_from_scval(&event.topics[3]);
+                                let old_id: Bytes = env.from_scval(&event.data);
+                                let old_id = bytes_to_vec(old_id);
+                                let older: Vec<Signers> = env
+                                    .read_filter()
+                                    .column_equal_to("id", old_id.clone())
+                                    .read()
+                                    .unwrap();
+                                let mut older = older[0].clone();
+
+                                older.id = id;
+                                older.kind = kind;
+                                older.pk = pk;
+                                older.date = env.reader().ledger_timestamp();
+
+                                env.update()
+                                    .column_equal_to("id", old_id)
+                                    .execute(&older)
+                                    .unwrap();
+                            } else if etype == Symbol::new(env.soroban(), "rec_start") {
+                                let id: Bytes = env.from_scval(&event.topics[2]);
+                                let pk: BytesN<65> = env.from_scval(&event.topics[3]);
+                                let execute_after: u32 = env.from_scval(&event.data);
+                                let recovery = Recoveries {
+                                    address: stellar_strkey::Contract(event.contract).to_string(),
+                                    id: bytes_to_vec(id),
+                                    pk: pk.to_array().to_vec(),
+                                    date: env.reader().ledger_timestamp(),
+                                    execute_after,
+                                    status: "pending".into(),
+                                };
+
+                                env.put(&recovery);
+                            } else if etype == Symbol::new(env.soroban(), "rec_stop")
+                                || etype == Symbol::new(env.soroban(), "rec_done")
+                            {
+                                let address = stellar_strkey::Contract(event.contract).to_string();
+                                let older: Vec<Recoveries> = env
+                                    .read_filter()
+                                    .column_equal_to("address", address.clone())
+                                    .column_equal_to("status", "pending".to_string())
+                                    .read()
+                                    .unwrap();
+
+                                if let Some(older) = older.first() {
+                                    let mut older = older.clone();
+
+                                    older.status =
+                                        if etype == Symbol::new(env.soroban(), "rec_stop") {
+                                            "cancelled".into()
+                                        } else {
+                                            "finished".into()
+                                        };
+
+                                    env.update()
+                                        .column_equal_to("address", address)
+                                        .column_equal_to("status", "pending".to_string())
+                                        .execute(&older)
+                                        .unwrap();
+                                }
                             }
                         }
                     }
@@ -293,6 +480,20 @@ pub extern "C" fn get_events_by_address() {
     env.conclude(&events)
 }
 
+#[no_mangle]
+pub extern "C" fn get_recoveries_by_address() {
+    let env = EnvClient::empty();
+    let request: QueryByAddressRequest = env.read_request_body();
+    let recoveries: Vec<Recoveries> = env
+        .read_filter()
+        .column_equal_to("address", request.address)
+        .column_equal_to("status", "pending".to_string())
+        .read()
+        .unwrap();
+
+    env.conclude(&recoveries)
+}
+
 // TODO make a serverless function to deactivate signers by id
 
 ////
@@ -300,7 +501,7 @@ pub extern "C" fn get_events_by_address() {
 #[cfg(test)]
 mod test {
     use ledger_meta_factory::TransitionPretty;
-    use stellar_xdr::next::{ScBytes, ScSymbol, ScVal};
+    use stellar_xdr::next::{ScBytes, ScString, ScSymbol, ScVal, ScVec};
     use zephyr_sdk::testutils::TestHost;
 
     fn add_signature(transition: &mut TransitionPretty) {
@@ -312,13 +513,31 @@ mod test {
                     ScVal::Symbol(ScSymbol("sw_v1".try_into().unwrap())),
                     ScVal::Symbol(ScSymbol("add".try_into().unwrap())),
                     ScVal::Bytes(ScBytes([0; 20].try_into().unwrap())),
-                    ScVal::Bytes(ScBytes([0; 65].try_into().unwrap())),
+                    ScVal::Vec(Some(ScVec(
+                        [
+                            ScVal::Symbol(ScSymbol("Secp256r1".try_into().unwrap())),
+                            ScVal::Bytes(ScBytes([0; 65].try_into().unwrap())),
+                        ]
+                        .try_into()
+                        .unwrap(),
+                    ))),
                     // ScVal::Symbol(ScSymbol("init".try_into().unwrap())),
                 ],
-                ScVal::Bool(true), // (
-                                   //     ScVal::Bytes(ScBytes([0; 65].try_into().unwrap())),
-                                   //     ScVal::Bool(true)
-                                   // ).try_into().unwrap(),
+                ScVal::Vec(Some(ScVec(
+                    [
+                        ScVal::Bool(true),
+                        ScVal::U32(1),
+                        ScVal::Void,
+                        ScVal::Void,
+                        ScVal::String(ScString("iPhone".try_into().unwrap())),
+                        ScVal::U32(8891),
+                    ]
+                    .try_into()
+                    .unwrap(),
+                ))), // (
+                     //     ScVal::Bytes(ScBytes([0; 65].try_into().unwrap())),
+                     //     ScVal::Bool(true)
+                     // ).try_into().unwrap(),
             )
             .unwrap();
     }
@@ -336,7 +555,29 @@ mod test {
             .load_table(
                 0,
                 "signers",
-                vec!["address", "id", "pk", "date", "admin", "active"],
+                vec![
+                    "address",
+                    "id",
+                    "kind",
+                    "pk",
+                    "date",
+                    "admin",
+                    "weight",
+                    "valid_after",
+                    "valid_until",
+                    "label",
+                    "created",
+                    "last_used",
+                    "active",
+                ],
+                None,
+            )
+            .await;
+        let _ = db
+            .load_table(
+                0,
+                "recoveries",
+                vec!["address", "id", "pk", "date", "execute_after", "status"],
                 None,
             )
             .await;